@@ -0,0 +1,684 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Convention, DirError, EnsuredDir, Explanation, Platform, Portable, Resolver, Source, service, xdg};
+
+const CACHE: &str = "cache";
+const CONFIG: &str = "config";
+const DATA: &str = "data";
+const LOGS: &str = "logs";
+const STATE: &str = "state";
+
+/// Application-scoped directories.
+///
+/// Appends an application-specific component to each base directory, following the naming
+/// convention of the resolver's [`Platform`]:
+/// - **Linux**: the lowercased application name with spaces replaced by `-` (e.g. `~/.config/bar-app`)
+/// - **macOS**: the reverse-DNS bundle identifier (e.g. `~/Library/Application Support/com.Foo-Corp.Bar-App`)
+/// - **Windows**: `Organization\Application` (e.g. `%APPDATA%\Foo Corp\Bar App`)
+///
+/// When a base directory comes from its XDG environment variable, the Linux naming convention is used on every platform
+/// so that `XDG_CONFIG_HOME=~/.config` yields `~/.config/bar-app` rather than a bundle identifier nested under the
+/// user's XDG directory. Directories the platform never reads from an XDG variable, such as `%LOCALAPPDATA%` or
+/// `~/Library/Preferences`, keep the native name. The same applies to every directory when the [`Resolver`] passed to
+/// [`with_resolver()`](Self::with_resolver) uses [`Convention::Xdg`].
+///
+/// Under [`Convention::Service`], directories that systemd exported for the unit, such as `$STATE_DIRECTORY`, are
+/// used as-is, and the Linux name is appended to the system locations on Linux and macOS.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::AppDirs;
+/// let app = AppDirs::new("com", "Foo Corp", "Bar App");
+/// if let Some(config) = app.config() {
+///     println!("Config directory: {}", config.display());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AppDirs {
+  application: String,
+  organization: String,
+  portable: Option<Portable>,
+  qualifier: String,
+  resolver: Resolver,
+}
+
+impl AppDirs {
+  /// Creates application-scoped directories for the given qualifier, organization, and application.
+  ///
+  /// The qualifier is usually a top-level domain such as `com` or `org`. The qualifier and organization
+  /// may be empty, in which case they are omitted from the platform-specific names.
+  pub fn new(qualifier: impl Into<String>, organization: impl Into<String>, application: impl Into<String>) -> Self {
+    Self {
+      application: application.into(),
+      organization: organization.into(),
+      portable: None,
+      qualifier: qualifier.into(),
+      resolver: Resolver::default(),
+    }
+  }
+
+  /// Returns the application name.
+  pub fn application(&self) -> &str {
+    &self.application
+  }
+
+  /// Returns the application's cache directory, based on [`cache_home()`](crate::cache_home).
+  ///
+  /// On Windows the native location is `%LOCALAPPDATA%\Organization\Application\cache` so that the cache
+  /// does not share a directory with [`state()`](Self::state).
+  pub fn cache(&self) -> Option<PathBuf> {
+    self.try_cache().ok()
+  }
+
+  /// Returns the application's configuration directory, based on [`config_home()`](crate::config_home).
+  pub fn config(&self) -> Option<PathBuf> {
+    self.try_config().ok()
+  }
+
+  /// Returns the application's data directory, based on [`data_home()`](crate::data_home).
+  pub fn data(&self) -> Option<PathBuf> {
+    self.try_data().ok()
+  }
+
+  /// Returns the application's local (non-roaming) data directory, based on
+  /// [`data_local()`](crate::data_local).
+  pub fn data_local(&self) -> Option<PathBuf> {
+    self.try_data_local().ok()
+  }
+
+  /// Returns the application's [cache directory](Self::cache), creating it if it does not exist.
+  ///
+  /// Missing directories, including the base directory, are created as described in
+  /// [`ensure_config_home()`](crate::ensure_config_home).
+  pub fn ensure_cache(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_cache())
+  }
+
+  /// Returns the application's [configuration directory](Self::config), creating it if it does not exist.
+  ///
+  /// # Examples
+  ///
+  /// ```rust,no_run
+  /// use dir_spec::AppDirs;
+  ///
+  /// let app = AppDirs::new("com", "Foo Corp", "Bar App");
+  /// if let Ok(config) = app.ensure_config() {
+  ///     let settings = config.path().join("settings.toml");
+  /// }
+  /// ```
+  pub fn ensure_config(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_config())
+  }
+
+  /// Returns the application's [data directory](Self::data), creating it if it does not exist.
+  pub fn ensure_data(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_data())
+  }
+
+  /// Returns the application's [local data directory](Self::data_local), creating it if it does not exist.
+  pub fn ensure_data_local(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_data_local())
+  }
+
+  /// Returns the application's [log directory](Self::log), creating it if it does not exist.
+  pub fn ensure_log(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_log())
+  }
+
+  /// Returns the application's [state directory](Self::state), creating it if it does not exist.
+  pub fn ensure_state(&self) -> Result<EnsuredDir, DirError> {
+    self.resolver.ensure(self.try_state())
+  }
+
+  /// Returns the application's log directory.
+  ///
+  /// Platform defaults:
+  /// - **Linux**: `~/.local/state/<app>/logs`, as the XDG spec treats logs as state
+  /// - **macOS**: `~/Library/Logs/<bundle id>`
+  /// - **Windows**: `%LOCALAPPDATA%\Organization\Application\Logs`
+  ///
+  /// When `XDG_STATE_HOME` is set or the resolver uses [`Convention::Xdg`], `<state>/<app>/logs` is used on
+  /// every platform. Under [`Convention::Service`], `$LOGS_DIRECTORY` is used as-is, falling back to
+  /// `/var/log/<app>`, `/Library/Logs/<app>` on macOS, or `%PROGRAMDATA%\Organization\Application\Logs` on Windows.
+  pub fn log(&self) -> Option<PathBuf> {
+    self.try_log().ok()
+  }
+
+  /// Returns the organization name.
+  pub fn organization(&self) -> &str {
+    &self.organization
+  }
+
+  /// Returns the directory holding the application's files if [portable mode](Self::with_portable) is active.
+  pub fn portable_dir(&self) -> Option<PathBuf> {
+    self
+      .portable
+      .as_ref()
+      .and_then(|portable| portable.detect(self.resolver.environment()))
+  }
+
+  /// Returns the application's preferences directory, based on [`preferences()`](crate::preferences).
+  ///
+  /// In portable mode this is the [configuration directory](Self::config).
+  pub fn preferences(&self) -> Option<PathBuf> {
+    if let Some(path) = self.portable(CONFIG) {
+      return Some(path);
+    }
+
+    self
+      .scoped(xdg::CONFIG_HOME, self.resolver.explain_preferences())
+      .map(|(path, _)| path)
+      .ok()
+  }
+
+  /// Returns the qualifier.
+  pub fn qualifier(&self) -> &str {
+    &self.qualifier
+  }
+
+  /// Returns the application's runtime directory, based on [`runtime()`](crate::runtime).
+  pub fn runtime(&self) -> Option<PathBuf> {
+    self
+      .scoped(xdg::RUNTIME_DIR, self.resolver.explain_runtime())
+      .map(|(path, _)| path)
+      .ok()
+  }
+
+  /// Returns the application's state directory, based on [`state_home()`](crate::state_home).
+  pub fn state(&self) -> Option<PathBuf> {
+    self.try_state().ok()
+  }
+
+  /// Keeps the application's files next to its executable when `portable` detects portable mode.
+  ///
+  /// While portable mode is active, [`config()`](Self::config), [`data()`](Self::data),
+  /// [`data_local()`](Self::data_local), [`cache()`](Self::cache), [`state()`](Self::state) and [`log()`](Self::log)
+  /// resolve beneath the executable's directory instead of the base directories.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{AppDirs, Portable};
+  ///
+  /// let app = AppDirs::new("com", "Foo Corp", "Bar App").with_portable(Portable::new());
+  /// let config = app.config(); // <exe dir>/config when portable.txt is next to the executable
+  /// ```
+  pub fn with_portable(mut self, portable: Portable) -> Self {
+    self.portable = Some(portable);
+    self
+  }
+
+  /// Resolves the base directories with `resolver` instead of the default resolver.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{AppDirs, Convention, Resolver};
+  ///
+  /// // ~/.config/bar-app on every platform
+  /// let app = AppDirs::new("com", "Foo Corp", "Bar App")
+  ///   .with_resolver(Resolver::new().with_convention(Convention::Xdg));
+  /// let config = app.config();
+  /// ```
+  pub fn with_resolver(mut self, resolver: Resolver) -> Self {
+    self.resolver = resolver;
+    self
+  }
+
+  fn bundle_id(&self) -> String {
+    [&self.qualifier, &self.organization, &self.application]
+      .into_iter()
+      .map(|part| part.split_whitespace().collect::<Vec<_>>().join("-"))
+      .filter(|part| !part.is_empty())
+      .collect::<Vec<_>>()
+      .join(".")
+  }
+
+  fn join(&self, base: impl AsRef<Path>, relative: impl AsRef<Path>) -> PathBuf {
+    self.resolver.platform().join(base, relative)
+  }
+
+  fn native_name(&self) -> String {
+    match self.resolver.platform() {
+      Platform::MacOs => self.bundle_id(),
+      Platform::Unix => self.xdg_name(),
+      Platform::Windows => [&self.organization, &self.application]
+        .into_iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\\"),
+    }
+  }
+
+  fn portable(&self, relative: &str) -> Option<PathBuf> {
+    self.portable_dir().map(|dir| self.join(dir, relative))
+  }
+
+  fn scoped(&self, key: &str, base: Explanation) -> Result<(PathBuf, bool), DirError> {
+    let xdg = self.uses_xdg_name(key, &base);
+    let base = base.into_result();
+    if self.uses_service_directory(service_key(key)) {
+      base.map(|path| (path, false))
+    } else if xdg {
+      base.map(|path| (self.join(path, self.xdg_name()), false))
+    } else {
+      base.map(|path| (self.join(path, self.native_name()), true))
+    }
+  }
+
+  fn try_cache(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(CACHE) {
+      return Ok(path);
+    }
+
+    self
+      .scoped(xdg::CACHE_HOME, self.resolver.explain_cache_home())
+      .map(|(path, native)| {
+        if native && self.resolver.platform() == Platform::Windows {
+          self.join(path, "cache")
+        } else {
+          path
+        }
+      })
+  }
+
+  fn try_config(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(CONFIG) {
+      return Ok(path);
+    }
+
+    self
+      .scoped(xdg::CONFIG_HOME, self.resolver.explain_config_home())
+      .map(|(path, _)| path)
+  }
+
+  fn try_data(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(DATA) {
+      return Ok(path);
+    }
+
+    self
+      .scoped(xdg::DATA_HOME, self.resolver.explain_data_home())
+      .map(|(path, _)| path)
+  }
+
+  fn try_data_local(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(DATA) {
+      return Ok(path);
+    }
+
+    self
+      .scoped(xdg::DATA_HOME, self.resolver.explain_data_local())
+      .map(|(path, _)| path)
+  }
+
+  fn try_log(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(STATE) {
+      return Ok(self.join(path, LOGS));
+    }
+
+    let logs = self.resolver.explain_logs();
+    let xdg = self.uses_xdg_name(xdg::STATE_HOME, &logs);
+    let logs = logs.into_result();
+    if self.uses_service_directory(service::LOGS_DIRECTORY) {
+      return logs;
+    }
+
+    logs.map(|base| match (self.resolver.convention(), self.resolver.platform()) {
+      (_, Platform::Windows) if !xdg => self.join(self.join(base, self.native_name()), "Logs"),
+      (Convention::Service, _) => self.join(base, self.xdg_name()),
+      _ if xdg => self.join(self.join(base, self.xdg_name()), LOGS),
+      (_, Platform::MacOs) => self.join(base, self.native_name()),
+      (_, Platform::Unix | Platform::Windows) => self.join(self.join(base, self.native_name()), LOGS),
+    })
+  }
+
+  fn try_state(&self) -> Result<PathBuf, DirError> {
+    if let Some(path) = self.portable(STATE) {
+      return Ok(path);
+    }
+
+    self
+      .scoped(xdg::STATE_HOME, self.resolver.explain_state_home())
+      .map(|(path, _)| path)
+  }
+
+  fn uses_service_directory(&self, key: &str) -> bool {
+    self.resolver.convention() == Convention::Service
+      && service::directory(self.resolver.platform(), self.resolver.environment(), key).is_some()
+  }
+
+  /// Returns `true` if the Linux name is used beneath `base`, which happens under [`Convention::Native`] only when
+  /// `base` was read from the XDG variable `key`.
+  fn uses_xdg_name(&self, key: &str, base: &Explanation) -> bool {
+    match self.resolver.convention() {
+      Convention::Native => matches!(base.source(), Some(Source::Var { name, .. }) if name == key),
+      Convention::Service => self.resolver.platform() != Platform::Windows,
+      Convention::Xdg => true,
+    }
+  }
+
+  fn xdg_name(&self) -> String {
+    self
+      .application
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join("-")
+      .to_lowercase()
+  }
+}
+
+/// Maps an XDG variable to the variable systemd exports for the same kind of directory.
+fn service_key(key: &str) -> &'static str {
+  match key {
+    xdg::CACHE_HOME => service::CACHE_DIRECTORY,
+    xdg::CONFIG_HOME => service::CONFIGURATION_DIRECTORY,
+    xdg::RUNTIME_DIR => service::RUNTIME_DIRECTORY,
+    _ => service::STATE_DIRECTORY,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod bundle_id {
+    use super::*;
+
+    #[test]
+    fn it_joins_parts_with_dots_and_dashes() {
+      let app = AppDirs::new("com", "Foo Corp", "Bar App");
+
+      assert_eq!(app.bundle_id(), "com.Foo-Corp.Bar-App");
+    }
+
+    #[test]
+    fn it_skips_empty_parts() {
+      let app = AppDirs::new("", "", "Bar App");
+
+      assert_eq!(app.bundle_id(), "Bar-App");
+    }
+  }
+
+  mod cache {
+    use temp_env::with_var;
+
+    use super::*;
+
+    #[test]
+    fn it_uses_xdg_name_when_xdg_cache_home_is_set() {
+      let base = if cfg!(windows) {
+        "C:\\test\\cache"
+      } else {
+        "/test/cache"
+      };
+      let app = AppDirs::new("com", "Foo Corp", "Bar App");
+
+      with_var("XDG_CACHE_HOME", Some(base), || {
+        assert_eq!(app.cache(), Some(PathBuf::from(base).join("bar-app")));
+      });
+    }
+
+    #[test]
+    fn it_uses_native_name_when_xdg_cache_home_is_unset() {
+      let app = AppDirs::new("com", "Foo Corp", "Bar App");
+
+      with_var("XDG_CACHE_HOME", None::<&str>, || {
+        let expected = crate::cache_home().map(|base| {
+          let path = base.join(app.native_name());
+          if cfg!(windows) { path.join("cache") } else { path }
+        });
+        assert_eq!(app.cache(), expected);
+      });
+    }
+  }
+
+  mod config {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_uses_xdg_name_with_xdg_convention() {
+      let resolver = Resolver::new()
+        .with_environment(MapEnvironment::new().with_home("/home/alice"))
+        .with_convention(Convention::Xdg);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(app.config(), Some(PathBuf::from("/home/alice/.config/bar-app")));
+    }
+
+    #[test]
+    fn it_uses_configuration_directory_as_is_with_service_convention() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new().with_var("CONFIGURATION_DIRECTORY", "/etc/bar-app:/etc/bar-app.d"))
+        .with_convention(Convention::Service);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(app.config(), Some(PathBuf::from("/etc/bar-app")));
+    }
+
+    #[test]
+    fn it_uses_etc_with_service_convention() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new())
+        .with_convention(Convention::Service);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(app.config(), Some(PathBuf::from("/etc/bar-app")));
+      assert_eq!(app.state(), Some(PathBuf::from("/var/lib/bar-app")));
+    }
+  }
+
+  mod data_local {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_uses_native_name_on_windows_when_xdg_data_home_is_set() {
+      let env = MapEnvironment::new()
+        .with_var("LOCALAPPDATA", "C:\\Users\\a\\AppData\\Local")
+        .with_var("XDG_DATA_HOME", "C:\\Users\\a\\.local\\share");
+      let resolver = Resolver::new().with_platform(Platform::Windows).with_environment(env);
+      let app = AppDirs::new("com", "Org", "App").with_resolver(resolver);
+
+      assert_eq!(
+        app.data_local().map(|path| path.to_string_lossy().into_owned()),
+        Some("C:\\Users\\a\\AppData\\Local\\Org\\App".to_string())
+      );
+      assert_eq!(
+        app.data().map(|path| path.to_string_lossy().into_owned()),
+        Some("C:\\Users\\a\\.local\\share\\app".to_string())
+      );
+    }
+  }
+
+  mod ensure_config {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_creates_the_application_directory() {
+      let temp = tempfile::tempdir().unwrap();
+      let base = temp.path();
+      let resolver = Resolver::new().with_environment(MapEnvironment::new().with_var("XDG_CONFIG_HOME", base));
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      let first = app.ensure_config().unwrap();
+      let second = app.ensure_config().unwrap();
+
+      assert_eq!(first.path(), base.join("bar-app"));
+      assert!(first.created());
+      assert!(!second.created());
+    }
+
+    #[test]
+    fn it_reports_why_the_base_directory_is_missing() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new());
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(app.ensure_config(), Err(DirError::MissingHome));
+    }
+  }
+
+  mod log {
+    use temp_env::with_var;
+
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_uses_library_logs_on_macos() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::MacOs)
+        .with_environment(MapEnvironment::new().with_home("/Users/alice"));
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(
+        app.log(),
+        Some(PathBuf::from("/Users/alice/Library/Logs/com.Foo-Corp.Bar-App"))
+      );
+    }
+
+    #[test]
+    fn it_uses_local_app_data_on_windows() {
+      let env = MapEnvironment::new().with_var("LOCALAPPDATA", "C:\\Users\\alice\\AppData\\Local");
+      let resolver = Resolver::new().with_platform(Platform::Windows).with_environment(env);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(
+        app.log().map(|path| path.to_string_lossy().into_owned()),
+        Some("C:\\Users\\alice\\AppData\\Local\\Foo Corp\\Bar App\\Logs".to_string())
+      );
+    }
+
+    #[test]
+    fn it_uses_logs_directory_with_service_convention() {
+      let env = MapEnvironment::new().with_var("LOGS_DIRECTORY", "/var/log/bar-app");
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(env)
+        .with_convention(Convention::Service);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(app.log(), Some(PathBuf::from("/var/log/bar-app")));
+    }
+
+    #[test]
+    fn it_uses_program_data_with_service_convention_on_windows() {
+      let env = MapEnvironment::new().with_var("PROGRAMDATA", "C:\\ProgramData");
+      let resolver = Resolver::new()
+        .with_platform(Platform::Windows)
+        .with_environment(env)
+        .with_convention(Convention::Service);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(
+        app.log().map(|path| path.to_string_lossy().into_owned()),
+        Some("C:\\ProgramData\\Foo Corp\\Bar App\\Logs".to_string())
+      );
+    }
+
+    #[test]
+    fn it_uses_xdg_state_home_when_set() {
+      let base = if cfg!(windows) {
+        "C:\\test\\state"
+      } else {
+        "/test/state"
+      };
+      let app = AppDirs::new("com", "Foo Corp", "Bar App");
+
+      with_var("XDG_STATE_HOME", Some(base), || {
+        assert_eq!(app.log(), Some(PathBuf::from(base).join("bar-app").join("logs")));
+      });
+    }
+  }
+
+  mod native_name {
+    use super::*;
+
+    #[test]
+    fn it_follows_platform_convention() {
+      let app =
+        |platform| AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(Resolver::new().with_platform(platform));
+
+      assert_eq!(app(Platform::MacOs).native_name(), "com.Foo-Corp.Bar-App");
+      assert_eq!(app(Platform::Unix).native_name(), "bar-app");
+      assert_eq!(app(Platform::Windows).native_name(), "Foo Corp\\Bar App");
+    }
+  }
+
+  mod portable_dir {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_moves_every_directory_next_to_the_executable() {
+      let dir = PathBuf::from("/opt/bar-app");
+      let resolver = Resolver::new().with_environment(
+        MapEnvironment::new()
+          .with_home("/home/alice")
+          .with_var("BAR_APP_PORTABLE", "1"),
+      );
+      let app = AppDirs::new("com", "Foo Corp", "Bar App")
+        .with_resolver(resolver)
+        .with_portable(Portable::new().with_dir(&dir).with_var("BAR_APP_PORTABLE"));
+
+      assert_eq!(app.portable_dir(), Some(dir.clone()));
+      assert_eq!(app.config(), Some(dir.join("config")));
+      assert_eq!(app.data(), Some(dir.join("data")));
+      assert_eq!(app.cache(), Some(dir.join("cache")));
+      assert_eq!(app.state(), Some(dir.join("state")));
+      assert_eq!(app.log(), Some(dir.join("state").join("logs")));
+    }
+
+    #[test]
+    fn it_uses_the_base_directories_when_inactive() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new().with_home("/home/alice"));
+      let app = AppDirs::new("com", "Foo Corp", "Bar App")
+        .with_resolver(resolver)
+        .with_portable(Portable::new().without_marker().with_var("BAR_APP_PORTABLE"));
+
+      assert_eq!(app.portable_dir(), None);
+      assert_eq!(app.config(), Some(PathBuf::from("/home/alice/.config/bar-app")));
+    }
+  }
+
+  mod preferences {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_uses_bundle_id_on_macos_when_xdg_config_home_is_set() {
+      let env = MapEnvironment::new()
+        .with_home("/Users/alice")
+        .with_var("XDG_CONFIG_HOME", "/Users/alice/.config");
+      let resolver = Resolver::new().with_platform(Platform::MacOs).with_environment(env);
+      let app = AppDirs::new("com", "Foo Corp", "Bar App").with_resolver(resolver);
+
+      assert_eq!(
+        app.preferences(),
+        Some(PathBuf::from("/Users/alice/Library/Preferences/com.Foo-Corp.Bar-App"))
+      );
+      assert_eq!(app.config(), Some(PathBuf::from("/Users/alice/.config/bar-app")));
+    }
+  }
+
+  mod xdg_name {
+    use super::*;
+
+    #[test]
+    fn it_lowercases_and_replaces_spaces() {
+      let app = AppDirs::new("com", "Foo Corp", "  Bar   App ");
+
+      assert_eq!(app.xdg_name(), "bar-app");
+    }
+  }
+}
@@ -0,0 +1,189 @@
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Environment, Explanation, Source, environment, home::HomeEnvironment, platform::dispatch, xdg};
+
+/// A location an application's files could be in, listed by
+/// [`Resolver::config_candidates()`](crate::Resolver::config_candidates).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Candidate {
+  exists: bool,
+  path: PathBuf,
+  selected: bool,
+  source: Source,
+}
+
+impl Candidate {
+  /// Returns `true` if something exists at the location, checked beneath the resolver's
+  /// [root](crate::Resolver::root) if it has one.
+  pub fn exists(&self) -> bool {
+    self.exists
+  }
+
+  /// Returns the location.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Returns `true` if this is the location the resolver currently selects.
+  pub fn selected(&self) -> bool {
+    self.selected
+  }
+
+  /// Returns where the base directory of the location came from, such as `$XDG_CONFIG_HOME` or the platform fallback
+  /// beneath the home directory.
+  pub fn source(&self) -> &Source {
+    &self.source
+  }
+}
+
+/// An [`Environment`] that hides one variable, used to find the fallbacks it overrides.
+#[derive(Debug)]
+struct Without<'a> {
+  env: &'a dyn Environment,
+  key: &'a str,
+}
+
+impl Environment for Without<'_> {
+  fn home_dir(&self) -> Option<PathBuf> {
+    self.env.home_dir()
+  }
+
+  fn root(&self) -> Option<&Path> {
+    self.env.root()
+  }
+
+  fn var_os(&self, key: &str) -> Option<OsString> {
+    if key == self.key { None } else { self.env.var_os(key) }
+  }
+}
+
+/// Lists the selected configuration directory for `name` followed by the native and XDG fallbacks it may override,
+/// without duplicates.
+pub fn config(home_env: HomeEnvironment<'_>, selected: Explanation, name: &Path) -> Vec<Candidate> {
+  let platform = home_env.platform;
+  let hidden = Without {
+    env: home_env.env,
+    key: xdg::CONFIG_HOME,
+  };
+  let fallback = HomeEnvironment {
+    env: &hidden,
+    ..home_env
+  };
+  let native = dispatch!(platform, config_home(&fallback));
+  let xdg = xdg::config_home(platform, &fallback);
+
+  let mut candidates: Vec<Candidate> = Vec::new();
+  for (explanation, selected) in [(selected, true), (native, false), (xdg, false)] {
+    let (Some(base), Some(source)) = (explanation.path(), explanation.source()) else {
+      continue;
+    };
+    let path = platform.join(base, name);
+    if candidates.iter().all(|candidate| candidate.path != path) {
+      candidates.push(Candidate {
+        exists: environment::beneath(home_env.root, &path).exists(),
+        path,
+        selected,
+        source: source.clone(),
+      });
+    }
+  }
+  candidates
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{MapEnvironment, Platform, Resolver, passwd::tests::image_root};
+
+  fn paths(candidates: &[Candidate]) -> Vec<&Path> {
+    candidates.iter().map(Candidate::path).collect()
+  }
+
+  mod config {
+    use super::*;
+
+    #[test]
+    fn it_lists_native_and_xdg_locations_on_macos() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::MacOs)
+        .with_environment(MapEnvironment::new().with_home("/Users/alice"));
+
+      let candidates = resolver.config_candidates("bar-app");
+
+      assert_eq!(
+        paths(&candidates),
+        [
+          Path::new("/Users/alice/Library/Application Support/bar-app"),
+          Path::new("/Users/alice/.config/bar-app")
+        ]
+      );
+      assert!(candidates[0].selected());
+      assert!(!candidates[1].selected());
+      assert_eq!(candidates[1].source(), &Source::home(".config"));
+    }
+
+    #[test]
+    fn it_lists_the_xdg_override_first() {
+      let env = MapEnvironment::new()
+        .with_home("/Users/alice")
+        .with_var("XDG_CONFIG_HOME", "/Users/alice/dotfiles");
+      let resolver = Resolver::new().with_platform(Platform::MacOs).with_environment(env);
+
+      let candidates = resolver.config_candidates("bar-app");
+
+      assert_eq!(
+        paths(&candidates),
+        [
+          Path::new("/Users/alice/dotfiles/bar-app"),
+          Path::new("/Users/alice/Library/Application Support/bar-app"),
+          Path::new("/Users/alice/.config/bar-app")
+        ]
+      );
+      assert!(candidates[0].selected());
+    }
+
+    #[test]
+    fn it_merges_identical_locations_on_linux() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new().with_home("/home/alice"));
+
+      let candidates = resolver.config_candidates("bar-app");
+
+      assert_eq!(paths(&candidates), [Path::new("/home/alice/.config/bar-app")]);
+      assert!(candidates[0].selected());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_reports_which_locations_exist() {
+      let temp = tempfile::tempdir().unwrap();
+      let home = temp.path();
+      std::fs::create_dir_all(home.join(".config/bar-app")).unwrap();
+      let resolver = Resolver::new()
+        .with_platform(Platform::MacOs)
+        .with_environment(MapEnvironment::new().with_home(home));
+
+      let candidates = resolver.config_candidates("bar-app");
+
+      assert!(!candidates[0].exists());
+      assert!(candidates[1].exists());
+    }
+
+    #[test]
+    fn it_reports_which_locations_exist_beneath_the_root() {
+      let temp = image_root();
+      let root = temp.path();
+      std::fs::create_dir_all(root.join("home/alice/.config/bar-app")).unwrap();
+      let alice = Resolver::for_user_in(root, "alice").unwrap();
+
+      let candidates = alice.config_candidates("bar-app");
+
+      assert_eq!(paths(&candidates), [Path::new("/home/alice/.config/bar-app")]);
+      assert!(candidates[0].exists());
+    }
+  }
+}
@@ -0,0 +1,230 @@
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::{
+  fs::{self, DirBuilder},
+  io::ErrorKind,
+  path::{Path, PathBuf},
+};
+
+use crate::{DirError, RejectionReason};
+
+#[cfg(unix)]
+const PRIVATE_MODE: u32 = 0o700;
+
+/// A directory returned by the `ensure_*` functions, such as [`ensure_config_home()`](crate::ensure_config_home).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EnsuredDir {
+  created: bool,
+  path: PathBuf,
+}
+
+impl EnsuredDir {
+  /// Returns `true` if the directory did not exist and was created.
+  pub fn created(&self) -> bool {
+    self.created
+  }
+
+  /// Consumes the directory, returning its path.
+  pub fn into_path(self) -> PathBuf {
+    self.path
+  }
+
+  /// Returns the directory's path.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+}
+
+/// Creates `path` and any missing parents with mode `0700`.
+///
+/// Existing components must be owned by `user`, or by the effective user when `user` is `None`, or by root, so that a
+/// directory planted by another user is never written to. Existing directories keep their permissions.
+pub fn dir(path: PathBuf, user: Option<u32>) -> Result<EnsuredDir, DirError> {
+  #[cfg(unix)]
+  let uid = user.unwrap_or_else(crate::secure::effective_uid);
+  #[cfg(not(unix))]
+  let uid = user.unwrap_or(0);
+
+  dir_as(path, uid)
+}
+
+fn check_owner(path: &Path, uid: u32) -> Result<(), DirError> {
+  let insecure = |reason| DirError::Insecure {
+    path: path.to_path_buf(),
+    reason,
+  };
+
+  let metadata = fs::metadata(path).map_err(|_| insecure(RejectionReason::NotFound))?;
+  if !metadata.is_dir() {
+    return Err(insecure(RejectionReason::NotDirectory));
+  }
+
+  #[cfg(unix)]
+  for owner in [
+    fs::symlink_metadata(path).map_or(metadata.uid(), |link| link.uid()),
+    metadata.uid(),
+  ] {
+    if owner != uid && owner != 0 {
+      return Err(insecure(RejectionReason::UnsafeOwner {
+        uid: owner,
+      }));
+    }
+  }
+  #[cfg(not(unix))]
+  let _ = uid;
+
+  Ok(())
+}
+
+fn create(path: &Path) -> Result<bool, DirError> {
+  let mut builder = DirBuilder::new();
+  #[cfg(unix)]
+  builder.mode(PRIVATE_MODE);
+
+  match builder.create(path) {
+    Ok(()) => {
+      #[cfg(unix)]
+      fs::set_permissions(path, fs::Permissions::from_mode(PRIVATE_MODE)).map_err(|error| DirError::Create {
+        kind: error.kind(),
+        path: path.to_path_buf(),
+      })?;
+      Ok(true)
+    }
+    Err(error) if error.kind() == ErrorKind::AlreadyExists => Ok(false),
+    Err(error) => Err(DirError::Create {
+      kind: error.kind(),
+      path: path.to_path_buf(),
+    }),
+  }
+}
+
+fn dir_as(path: PathBuf, uid: u32) -> Result<EnsuredDir, DirError> {
+  let mut created = false;
+  let ancestors: Vec<&Path> = path.ancestors().filter(|p| !p.as_os_str().is_empty()).collect();
+  for component in ancestors.into_iter().rev() {
+    if fs::symlink_metadata(component).is_err() {
+      created = create(component)?;
+    }
+    check_owner(component, uid)?;
+  }
+
+  Ok(EnsuredDir {
+    created,
+    path,
+  })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+
+  mod dir {
+    use super::*;
+
+    #[test]
+    fn it_checks_ownership_against_the_given_user() {
+      use std::os::unix::fs::chown;
+
+      let temp = tempfile::tempdir().unwrap();
+      let planted = temp.path().join("config");
+      fs::create_dir_all(&planted).unwrap();
+      let owner = match crate::secure::effective_uid() {
+        0 => {
+          chown(&planted, Some(4242), None).unwrap();
+          4242
+        }
+        current => current,
+      };
+
+      let result = dir(planted.join("bar-app"), Some(owner + 1));
+
+      assert!(matches!(
+        result,
+        Err(DirError::Insecure {
+          reason: RejectionReason::UnsafeOwner { uid },
+          ..
+        }) if uid == owner
+      ));
+      assert!(!planted.join("bar-app").exists());
+    }
+
+    #[test]
+    fn it_creates_missing_directories_with_mode_0700() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path().join("root");
+      let path = root.join("config").join("bar-app");
+
+      let ensured = dir(path.clone(), None).unwrap();
+
+      assert!(ensured.created());
+      assert_eq!(ensured.path(), path);
+      for created in [&root, &root.join("config"), &path] {
+        assert_eq!(
+          fs::metadata(created).unwrap().permissions().mode() & 0o777,
+          PRIVATE_MODE
+        );
+      }
+    }
+
+    #[test]
+    fn it_keeps_existing_directories_unchanged() {
+      let temp = tempfile::tempdir().unwrap();
+      let path = temp.path().to_path_buf();
+      fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+      let ensured = dir(path.clone(), None).unwrap();
+
+      assert!(!ensured.created());
+      assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o755);
+    }
+
+    #[test]
+    fn it_refuses_a_file_in_the_way() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      fs::write(root.join("config"), "").unwrap();
+
+      let result = dir(root.join("config").join("bar-app"), None);
+
+      assert_eq!(
+        result,
+        Err(DirError::Insecure {
+          path: root.join("config"),
+          reason: RejectionReason::NotDirectory,
+        })
+      );
+    }
+  }
+
+  mod dir_as {
+    use std::os::unix::fs::chown;
+
+    use super::*;
+
+    #[test]
+    fn it_refuses_directories_owned_by_other_users() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let planted = root.join("config");
+      fs::create_dir_all(&planted).unwrap();
+      let current = crate::secure::effective_uid();
+      let (owner, uid) = if current == 0 {
+        chown(&planted, Some(4242), None).unwrap();
+        (4242, current)
+      } else {
+        (current, current + 1)
+      };
+
+      let result = dir_as(planted.join("bar-app"), uid);
+
+      assert!(matches!(
+        result,
+        Err(DirError::Insecure {
+          reason: RejectionReason::UnsafeOwner { uid },
+          ..
+        }) if uid == owner
+      ));
+      assert!(!planted.join("bar-app").exists());
+    }
+  }
+}
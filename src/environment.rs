@@ -0,0 +1,235 @@
+use std::{
+  collections::HashMap,
+  env,
+  ffi::OsString,
+  fmt::Debug,
+  path::{Component, Path, PathBuf},
+};
+
+const HOME: &str = "HOME";
+const USERPROFILE: &str = "USERPROFILE";
+
+/// A source of environment variables and the home directory.
+///
+/// Directory resolution reads exclusively through this trait, so a [`Resolver`](crate::Resolver) can resolve
+/// directories on behalf of an environment other than the current process's.
+pub trait Environment: Debug + Send + Sync {
+  /// Returns the user's home directory.
+  fn home_dir(&self) -> Option<PathBuf>;
+
+  /// Returns the directory files such as `user-dirs.dirs` are read beneath, or `None` to read them from `/`.
+  ///
+  /// Resolved directories are still reported as seen from inside this root, such as an unpacked container image. A
+  /// [`Resolver`](crate::Resolver) using this environment treats it as its [`root()`](crate::Resolver::root) unless it
+  /// was created with [`for_user_in()`](crate::Resolver::for_user_in).
+  fn root(&self) -> Option<&Path> {
+    None
+  }
+
+  /// Returns the value of the environment variable `key`, if set.
+  fn var_os(&self, key: &str) -> Option<OsString>;
+}
+
+/// An [`Environment`] backed by an in-memory map.
+///
+/// Useful for deterministic tests that do not mutate the process environment, and for resolving
+/// directories from a captured environment such as a remote session's `env` output.
+///
+/// Unless set explicitly with [`with_home()`](Self::with_home), the home directory is read from the
+/// `HOME` variable, then from `USERPROFILE`.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{MapEnvironment, Resolver};
+///
+/// let env = MapEnvironment::new()
+///   .with_home("/home/alice")
+///   .with_var("XDG_CACHE_HOME", "/var/cache/alice");
+/// let resolver = Resolver::new().with_environment(env);
+///
+/// assert_eq!(resolver.cache_home(), Some("/var/cache/alice".into()));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MapEnvironment {
+  home: Option<PathBuf>,
+  vars: HashMap<String, OsString>,
+}
+
+impl MapEnvironment {
+  /// Creates an empty environment.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the home directory, overriding `HOME` and `USERPROFILE`.
+  pub fn with_home(mut self, home: impl Into<PathBuf>) -> Self {
+    self.home = Some(home.into());
+    self
+  }
+
+  /// Sets the environment variable `key` to `value`.
+  pub fn with_var(mut self, key: impl Into<String>, value: impl Into<OsString>) -> Self {
+    self.vars.insert(key.into(), value.into());
+    self
+  }
+}
+
+impl Environment for MapEnvironment {
+  fn home_dir(&self) -> Option<PathBuf> {
+    self.home.clone().or_else(|| {
+      [HOME, USERPROFILE]
+        .into_iter()
+        .filter_map(|key| self.vars.get(key))
+        .find(|value| !value.is_empty())
+        .map(PathBuf::from)
+    })
+  }
+
+  fn var_os(&self, key: &str) -> Option<OsString> {
+    self.vars.get(key).cloned()
+  }
+}
+
+impl<K: Into<String>, V: Into<OsString>> FromIterator<(K, V)> for MapEnvironment {
+  fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+    Self {
+      home: None,
+      vars: iter
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect(),
+    }
+  }
+}
+
+/// The [`Environment`] of the current process.
+///
+/// Reads variables with [`std::env::var_os`] and the home directory with [`std::env::home_dir`].
+/// This is the environment used by the module-level functions and by [`Resolver::new()`](crate::Resolver::new).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SystemEnvironment;
+
+impl Environment for SystemEnvironment {
+  fn home_dir(&self) -> Option<PathBuf> {
+    env::home_dir()
+  }
+
+  fn var_os(&self, key: &str) -> Option<OsString> {
+    env::var_os(key)
+  }
+}
+
+/// Returns where `path`, as seen from inside `root`, lives on the host.
+///
+/// `..` segments are resolved as they would be inside `root`, so they never climb above it.
+pub(crate) fn beneath(root: Option<&Path>, path: &Path) -> PathBuf {
+  let Some(root) = root else {
+    return path.to_path_buf();
+  };
+
+  let mut relative = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::Normal(name) => relative.push(name),
+      Component::ParentDir => {
+        relative.pop();
+      }
+      Component::CurDir | Component::Prefix(_) | Component::RootDir => {}
+    }
+  }
+  root.join(relative)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod beneath {
+    use super::*;
+
+    #[test]
+    fn it_joins_absolute_paths_to_the_root() {
+      assert_eq!(
+        beneath(Some(Path::new("/images/rootfs")), Path::new("/etc/xdg")),
+        PathBuf::from("/images/rootfs/etc/xdg")
+      );
+    }
+
+    #[test]
+    fn it_clamps_parent_dir_segments_at_the_root() {
+      let root = Path::new("/images/rootfs");
+
+      assert_eq!(
+        beneath(Some(root), Path::new("../../etc")),
+        PathBuf::from("/images/rootfs/etc")
+      );
+      assert_eq!(
+        beneath(Some(root), Path::new("/home/alice/../../../etc/passwd")),
+        PathBuf::from("/images/rootfs/etc/passwd")
+      );
+    }
+
+    #[test]
+    fn it_returns_the_path_without_a_root() {
+      assert_eq!(beneath(None, Path::new("/etc/xdg")), PathBuf::from("/etc/xdg"));
+    }
+  }
+
+  mod from_iter {
+    use super::*;
+
+    #[test]
+    fn it_collects_captured_variables() {
+      let env: MapEnvironment = [("HOME", "/home/alice"), ("XDG_CONFIG_HOME", "/etc/alice")]
+        .into_iter()
+        .collect();
+
+      assert_eq!(env.var_os("XDG_CONFIG_HOME"), Some(OsString::from("/etc/alice")));
+      assert_eq!(env.home_dir(), Some(PathBuf::from("/home/alice")));
+    }
+  }
+
+  mod home_dir {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_userprofile() {
+      let env = MapEnvironment::new().with_var("USERPROFILE", "C:\\Users\\alice");
+
+      assert_eq!(env.home_dir(), Some(PathBuf::from("C:\\Users\\alice")));
+    }
+
+    #[test]
+    fn it_ignores_empty_home() {
+      let env = MapEnvironment::new()
+        .with_var("HOME", "")
+        .with_var("USERPROFILE", "C:\\Users\\alice");
+
+      assert_eq!(env.home_dir(), Some(PathBuf::from("C:\\Users\\alice")));
+    }
+
+    #[test]
+    fn it_prefers_explicit_home() {
+      let env = MapEnvironment::new()
+        .with_home("/home/bob")
+        .with_var("HOME", "/home/alice");
+
+      assert_eq!(env.home_dir(), Some(PathBuf::from("/home/bob")));
+    }
+
+    #[test]
+    fn it_reads_home_variable() {
+      let env = MapEnvironment::new().with_var("HOME", "/home/alice");
+
+      assert_eq!(env.home_dir(), Some(PathBuf::from("/home/alice")));
+    }
+
+    #[test]
+    fn it_returns_none_without_home() {
+      let env = MapEnvironment::new();
+
+      assert_eq!(env.home_dir(), None);
+    }
+  }
+}
@@ -0,0 +1,201 @@
+use std::{
+  error::Error,
+  ffi::OsString,
+  fmt::{self, Display, Formatter},
+  io::ErrorKind,
+  path::PathBuf,
+};
+
+use crate::{Platform, Rejection, RejectionReason, User};
+
+/// Why a directory could not be resolved.
+///
+/// Returned by the `try_*` methods of [`Resolver`](crate::Resolver), such as
+/// [`try_config_home()`](crate::Resolver::try_config_home).
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{DirError, MapEnvironment, Resolver};
+///
+/// let resolver = Resolver::new().with_environment(MapEnvironment::new());
+///
+/// match resolver.try_cache_home() {
+///   Ok(path) => println!("{}", path.display()),
+///   Err(DirError::MissingHome) => eprintln!("set HOME to choose a cache directory"),
+///   Err(error) => eprintln!("{error}"),
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DirError {
+  /// A directory could not be created.
+  Create {
+    /// The kind of I/O error that occurred.
+    kind: ErrorKind,
+    /// The directory that could not be created.
+    path: PathBuf,
+  },
+  /// A directory the crate would create or reuse could be tampered with by another user.
+  Insecure {
+    /// The directory that was rejected.
+    path: PathBuf,
+    /// Why the directory was rejected.
+    reason: RejectionReason,
+  },
+  /// An environment variable was set to a value that cannot be used, and no fallback applied.
+  InvalidValue {
+    /// Why the value was rejected.
+    reason: RejectionReason,
+    /// The raw value of the variable.
+    value: OsString,
+    /// The name of the variable.
+    variable: String,
+  },
+  /// An entry could not be moved out of a legacy directory by [`Plan::execute()`](crate::Plan::execute).
+  Migrate {
+    /// The kind of I/O error that occurred.
+    kind: ErrorKind,
+    /// The entry or directory that could not be moved.
+    path: PathBuf,
+  },
+  /// The home directory could not be determined.
+  MissingHome,
+  /// A platform environment variable the directory is derived from, such as `%LOCALAPPDATA%`, is not set.
+  MissingVariable {
+    /// The name of the variable.
+    variable: String,
+  },
+  /// An environment variable was not valid Unicode and could not be interpreted with another platform's rules.
+  NotUnicode {
+    /// The raw value of the variable.
+    value: OsString,
+    /// The name of the variable.
+    variable: String,
+  },
+  /// A user account could not be found in the passwd database.
+  UnknownUser {
+    /// The account that was looked up.
+    user: User,
+  },
+  /// The platform has no such directory, such as [`fonts()`](crate::fonts) on Windows, or the resolver cannot
+  /// provide it, such as [`Resolver::ensure_config_home()`](crate::Resolver::ensure_config_home) beneath a root.
+  Unsupported {
+    /// The platform whose rules were followed.
+    platform: Platform,
+  },
+}
+
+impl Display for DirError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Create {
+        kind,
+        path,
+      } => write!(f, "failed to create `{}`: {kind}", path.display()),
+      Self::Insecure {
+        path,
+        reason,
+      } => write!(f, "refusing to use `{}`: {reason}", path.display()),
+      Self::InvalidValue {
+        reason,
+        value,
+        variable,
+      } => {
+        let value = value.to_string_lossy();
+        match reason {
+          RejectionReason::Empty => write!(f, "{variable} is set but empty"),
+          RejectionReason::NotDirectory => write!(f, "{variable} `{value}` is not a directory"),
+          RejectionReason::NotFound => write!(f, "{variable} `{value}` does not exist"),
+          RejectionReason::NotUnicode => write!(f, "{variable} is not valid Unicode"),
+          RejectionReason::RelativePath => write!(f, "{variable} must be an absolute path, got `{value}`"),
+          RejectionReason::Symlink => write!(f, "{variable} `{value}` must not be a symbolic link"),
+          RejectionReason::UnknownUser => write!(f, "{variable} `{value}` does not name a known user"),
+          RejectionReason::UnsafeMode {
+            mode,
+          } => write!(f, "{variable} `{value}` must have mode 0700, has {mode:04o}"),
+          RejectionReason::UnsafeOwner {
+            uid,
+          } => write!(
+            f,
+            "{variable} `{value}` must be owned by the current user, is owned by uid {uid}"
+          ),
+        }
+      }
+      Self::Migrate {
+        kind,
+        path,
+      } => write!(f, "failed to migrate `{}`: {kind}", path.display()),
+      Self::MissingHome => write!(f, "the home directory could not be determined"),
+      Self::MissingVariable {
+        variable,
+      } => write!(f, "{variable} is not set"),
+      Self::NotUnicode {
+        variable, ..
+      } => write!(f, "{variable} is not valid Unicode"),
+      Self::UnknownUser {
+        user,
+      } => write!(f, "no passwd entry for {user}"),
+      Self::Unsupported {
+        platform,
+      } => {
+        let name = match platform {
+          Platform::MacOs => "macOS",
+          Platform::Unix => "Unix",
+          Platform::Windows => "Windows",
+        };
+        write!(f, "the directory is not supported on {name}")
+      }
+    }
+  }
+}
+
+impl Error for DirError {}
+
+impl From<Rejection> for DirError {
+  fn from(rejection: Rejection) -> Self {
+    let (reason, value, variable) = rejection.into_parts();
+    match reason {
+      RejectionReason::NotUnicode => Self::NotUnicode {
+        value,
+        variable,
+      },
+      _ => Self::InvalidValue {
+        reason,
+        value,
+        variable,
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod from {
+    use super::*;
+
+    #[test]
+    fn it_converts_non_unicode_rejections_to_not_unicode() {
+      let rejection = Rejection::new("XDG_CONFIG_HOME", "/srv", RejectionReason::NotUnicode);
+
+      assert_eq!(
+        DirError::from(rejection),
+        DirError::NotUnicode {
+          value: "/srv".into(),
+          variable: "XDG_CONFIG_HOME".to_string(),
+        }
+      );
+    }
+
+    #[test]
+    fn it_converts_relative_rejections_to_invalid_value() {
+      let rejection = Rejection::new("XDG_CONFIG_HOME", "config", RejectionReason::RelativePath);
+
+      assert_eq!(
+        DirError::from(rejection).to_string(),
+        "XDG_CONFIG_HOME must be an absolute path, got `config`"
+      );
+    }
+  }
+}
@@ -0,0 +1,424 @@
+use std::{
+  ffi::OsString,
+  fmt::{self, Display, Formatter},
+  path::{Path, PathBuf},
+};
+
+use crate::{DirError, Environment, Platform};
+
+/// A resolved directory together with where it came from.
+///
+/// Returned by the `explain_*` methods of [`Resolver`](crate::Resolver), such as
+/// [`explain_config_home()`](crate::Resolver::explain_config_home). Useful for diagnosing why a directory
+/// resolved where it did, e.g. in a `--verbose` or `doctor` command.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{MapEnvironment, Resolver, Source};
+///
+/// let env = MapEnvironment::new()
+///   .with_home("/home/alice")
+///   .with_var("XDG_CONFIG_HOME", "relative/config");
+/// let explanation = Resolver::new().with_environment(env).explain_config_home();
+///
+/// for rejection in explanation.rejected() {
+///   println!("{rejection}"); // XDG_CONFIG_HOME ignored: relative path `relative/config`
+/// }
+/// if let Some(source) = explanation.source() {
+///   println!("{source}");
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+  rejected: Vec<Rejection>,
+  result: Result<PathBuf, DirError>,
+  source: Option<Source>,
+}
+
+impl Explanation {
+  pub(crate) fn resolved(path: impl Into<PathBuf>, source: Source) -> Self {
+    Self {
+      rejected: Vec::new(),
+      result: Ok(path.into()),
+      source: Some(source),
+    }
+  }
+
+  pub(crate) fn unresolved(error: DirError) -> Self {
+    Self {
+      rejected: Vec::new(),
+      result: Err(error),
+      source: None,
+    }
+  }
+
+  /// Resolves to the value of `key`, joined with `relative` when not empty, without validating it.
+  pub(crate) fn var(platform: Platform, env: &dyn Environment, key: &str, relative: &str) -> Self {
+    let Some(value) = env.var_os(key) else {
+      return Self::unresolved(DirError::MissingVariable {
+        variable: key.to_string(),
+      });
+    };
+
+    if !platform.is_representable(&value) {
+      return Self::unresolved(DirError::MissingVariable {
+        variable: key.to_string(),
+      })
+      .reject(Rejection::new(key, value, RejectionReason::NotUnicode));
+    }
+
+    let path = if relative.is_empty() {
+      PathBuf::from(&value)
+    } else {
+      platform.join(&value, relative)
+    };
+    Self::resolved(path, Source::var(key, value, relative))
+  }
+
+  /// Consumes the explanation, returning the resolved path.
+  pub fn into_path(self) -> Option<PathBuf> {
+    self.result.ok()
+  }
+
+  /// Consumes the explanation, returning the resolved path or why no candidate applied.
+  ///
+  /// When an environment variable was rejected and no fallback applied, the first rejection is reported
+  /// since it is usually the one the user needs to fix.
+  pub fn into_result(self) -> Result<PathBuf, DirError> {
+    match (self.result, self.rejected.into_iter().next()) {
+      (Err(_), Some(rejection)) => Err(rejection.into()),
+      (result, _) => result,
+    }
+  }
+
+  /// Returns the resolved path, or `None` if no candidate applied.
+  pub fn path(&self) -> Option<&Path> {
+    self.result.as_deref().ok()
+  }
+
+  /// Returns the candidates that were considered and rejected, in the order they were checked.
+  pub fn rejected(&self) -> &[Rejection] {
+    &self.rejected
+  }
+
+  /// Returns where the resolved path came from, or `None` if no candidate applied.
+  pub fn source(&self) -> Option<&Source> {
+    self.source.as_ref()
+  }
+
+  /// Returns `self` if it resolved, otherwise the result of `fallback` with the rejections of both.
+  pub(crate) fn or_else(self, fallback: impl FnOnce() -> Self) -> Self {
+    if self.result.is_ok() {
+      return self;
+    }
+
+    let mut next = fallback();
+    let mut rejected = self.rejected;
+    rejected.append(&mut next.rejected);
+    next.rejected = rejected;
+    next
+  }
+
+  pub(crate) fn reject(mut self, rejection: Rejection) -> Self {
+    self.rejected.push(rejection);
+    self
+  }
+}
+
+/// An environment variable that was set but ignored during resolution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rejection {
+  reason: RejectionReason,
+  value: OsString,
+  variable: String,
+}
+
+impl Rejection {
+  pub(crate) fn new(variable: impl Into<String>, value: impl Into<OsString>, reason: RejectionReason) -> Self {
+    Self {
+      reason,
+      value: value.into(),
+      variable: variable.into(),
+    }
+  }
+
+  pub(crate) fn into_parts(self) -> (RejectionReason, OsString, String) {
+    (self.reason, self.value, self.variable)
+  }
+
+  /// Returns why the value was ignored.
+  pub fn reason(&self) -> RejectionReason {
+    self.reason
+  }
+
+  /// Returns the raw value of the variable.
+  pub fn value(&self) -> &OsString {
+    &self.value
+  }
+
+  /// Returns the name of the variable.
+  pub fn variable(&self) -> &str {
+    &self.variable
+  }
+}
+
+impl Display for Rejection {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let (variable, value) = (&self.variable, self.value.to_string_lossy());
+    match self.reason {
+      RejectionReason::Empty => write!(f, "{variable} ignored: empty value"),
+      RejectionReason::NotDirectory => write!(f, "{variable} ignored: `{value}` is not a directory"),
+      RejectionReason::NotFound => write!(f, "{variable} ignored: `{value}` does not exist"),
+      RejectionReason::NotUnicode => write!(f, "{variable} ignored: `{value}` is not valid Unicode"),
+      RejectionReason::RelativePath => write!(f, "{variable} ignored: relative path `{value}`"),
+      RejectionReason::Symlink => write!(f, "{variable} ignored: `{value}` is a symbolic link"),
+      RejectionReason::UnknownUser => write!(f, "{variable} ignored: no passwd entry for `{value}`"),
+      RejectionReason::UnsafeMode {
+        mode,
+      } => {
+        write!(f, "{variable} ignored: `{value}` has mode {mode:04o}, expected 0700")
+      }
+      RejectionReason::UnsafeOwner {
+        uid,
+      } => write!(f, "{variable} ignored: `{value}` is owned by uid {uid}"),
+    }
+  }
+}
+
+/// Why an environment variable was ignored.
+///
+/// Besides the XDG rules for values, the secure resolvers such as
+/// [`secure_runtime()`](crate::Resolver::secure_runtime) reject directories that other users could tamper with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RejectionReason {
+  /// The variable was set to an empty string, which the XDG spec treats as unset.
+  Empty,
+  /// The path is not a directory.
+  NotDirectory,
+  /// The path does not exist or could not be inspected.
+  NotFound,
+  /// The variable was not valid Unicode, so it could not be interpreted with another platform's rules.
+  ///
+  /// Only reported when the [`Platform`] being resolved differs from the host's.
+  NotUnicode,
+  /// The variable held a relative path, which the XDG spec requires to be ignored.
+  RelativePath,
+  /// The path is a symbolic link, which could be redirected to a directory controlled by another user.
+  Symlink,
+  /// The variable named a user account that has no passwd entry.
+  UnknownUser,
+  /// The directory can be accessed by users other than its owner.
+  UnsafeMode {
+    /// The directory's permission bits, such as `0o755`.
+    mode: u32,
+  },
+  /// The directory is owned by a different user.
+  UnsafeOwner {
+    /// The user id of the directory's owner.
+    uid: u32,
+  },
+}
+
+impl Display for RejectionReason {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Empty => write!(f, "empty value"),
+      Self::NotDirectory => write!(f, "not a directory"),
+      Self::NotFound => write!(f, "not found"),
+      Self::NotUnicode => write!(f, "not valid Unicode"),
+      Self::RelativePath => write!(f, "relative path"),
+      Self::Symlink => write!(f, "symbolic link"),
+      Self::UnknownUser => write!(f, "unknown user"),
+      Self::UnsafeMode {
+        mode,
+      } => write!(f, "mode {mode:04o}, expected 0700"),
+      Self::UnsafeOwner {
+        uid,
+      } => write!(f, "owned by uid {uid}"),
+    }
+  }
+}
+
+/// Where a resolved directory came from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+  /// A fixed location that does not depend on the environment, such as `/tmp`.
+  Default,
+  /// The home directory, or `relative` beneath it when `relative` is not empty.
+  Home {
+    /// The platform fallback joined onto the home directory, such as `.config`.
+    relative: String,
+  },
+  /// The home directory of an account in the system's user database or a passwd file.
+  Passwd {
+    /// The passwd file the account was read from, such as `/images/rootfs/etc/passwd`, or `None` if it came from
+    /// the system's user database.
+    file: Option<PathBuf>,
+    /// The account's name.
+    user: String,
+    /// The environment variable that named the account, such as `SUDO_UID`, if any.
+    variable: Option<String>,
+  },
+  /// A private per-user directory beneath the shared temporary directory, used by
+  /// [`RuntimeFallback::Private`](crate::RuntimeFallback::Private).
+  PrivateRuntime {
+    /// Whether the directory was created rather than reused.
+    created: bool,
+  },
+  /// An entry in `user-dirs.dirs` or `user-dirs.defaults`.
+  UserDirs {
+    /// The file the entry was read from.
+    file: PathBuf,
+    /// The entry's key, such as `XDG_DOWNLOAD_DIR`.
+    key: String,
+  },
+  /// An environment variable, or `relative` beneath it when `relative` is not empty.
+  Var {
+    /// The name of the variable, such as `XDG_CONFIG_HOME`.
+    name: String,
+    /// The platform fallback joined onto the variable's value, such as `Programs`.
+    relative: String,
+    /// The raw value of the variable.
+    value: OsString,
+  },
+}
+
+impl Source {
+  pub(crate) fn home(relative: impl Into<String>) -> Self {
+    Self::Home {
+      relative: relative.into(),
+    }
+  }
+
+  pub(crate) fn var(name: impl Into<String>, value: impl Into<OsString>, relative: impl Into<String>) -> Self {
+    Self::Var {
+      name: name.into(),
+      relative: relative.into(),
+      value: value.into(),
+    }
+  }
+}
+
+impl Display for Source {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Default => write!(f, "platform default"),
+      Self::Home {
+        relative,
+      } if relative.is_empty() => write!(f, "home directory"),
+      Self::Home {
+        relative,
+      } => write!(f, "`{relative}` beneath the home directory"),
+      Self::Passwd {
+        file,
+        user,
+        variable,
+      } => {
+        match file {
+          Some(file) => write!(f, "home directory of `{user}` in {}", file.display())?,
+          None => write!(f, "home directory of `{user}` in the user database")?,
+        }
+        match variable {
+          Some(variable) => write!(f, " from {variable}"),
+          None => Ok(()),
+        }
+      }
+      Self::PrivateRuntime {
+        created,
+      } => write!(
+        f,
+        "private runtime directory ({})",
+        if *created { "created" } else { "reused" }
+      ),
+      Self::UserDirs {
+        file,
+        key,
+      } => write!(f, "{key} in {}", file.display()),
+      Self::Var {
+        name,
+        relative,
+        value,
+      } if relative.is_empty() => write!(f, "{name}=`{}`", value.to_string_lossy()),
+      Self::Var {
+        name,
+        relative,
+        value,
+      } => {
+        write!(f, "`{relative}` beneath {name}=`{}`", value.to_string_lossy())
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod or_else {
+    use super::*;
+
+    #[test]
+    fn it_keeps_a_resolved_explanation() {
+      let explanation = Explanation::resolved("/etc", Source::Default);
+
+      let result = explanation
+        .clone()
+        .or_else(|| Explanation::resolved("/tmp", Source::Default));
+
+      assert_eq!(result, explanation);
+    }
+
+    #[test]
+    fn it_keeps_rejections_from_both_candidates() {
+      let first = Rejection::new("XDG_CONFIG_HOME", "config", RejectionReason::RelativePath);
+      let second = Rejection::new("APPDATA", "", RejectionReason::Empty);
+
+      let result = Explanation::unresolved(DirError::MissingHome)
+        .reject(first.clone())
+        .or_else(|| Explanation::resolved("/home/alice/.config", Source::home(".config")).reject(second.clone()));
+
+      assert_eq!(result.path(), Some(Path::new("/home/alice/.config")));
+      assert_eq!(result.rejected(), [first, second]);
+    }
+  }
+
+  mod rejection {
+    use super::*;
+
+    #[test]
+    fn it_displays_the_variable_and_reason() {
+      let rejection = Rejection::new("XDG_CONFIG_HOME", "relative/config", RejectionReason::RelativePath);
+
+      assert_eq!(
+        rejection.to_string(),
+        "XDG_CONFIG_HOME ignored: relative path `relative/config`"
+      );
+    }
+  }
+
+  mod source {
+    use super::*;
+
+    #[test]
+    fn it_displays_home_fallbacks() {
+      assert_eq!(Source::home("").to_string(), "home directory");
+      assert_eq!(
+        Source::home(".config").to_string(),
+        "`.config` beneath the home directory"
+      );
+    }
+
+    #[test]
+    fn it_displays_variables() {
+      assert_eq!(
+        Source::var("XDG_CONFIG_HOME", "/srv/config", "").to_string(),
+        "XDG_CONFIG_HOME=`/srv/config`"
+      );
+      assert_eq!(
+        Source::var("LOCALAPPDATA", "C:\\Local", "Programs").to_string(),
+        "`Programs` beneath LOCALAPPDATA=`C:\\Local`"
+      );
+    }
+  }
+}
@@ -0,0 +1,313 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{Platform, Resolver, environment, xdg};
+
+const FLATPAK_ID: &str = "FLATPAK_ID";
+const INFO_FILE: &str = "/.flatpak-info";
+
+/// The Flatpak sandbox the process is running in.
+///
+/// Inside a sandbox, Flatpak points `XDG_CONFIG_HOME`, `XDG_DATA_HOME`, `XDG_CACHE_HOME` and `XDG_STATE_HOME` at
+/// directories beneath `~/.var/app/<app id>`, so the module-level functions and [`AppDirs`](crate::AppDirs) already
+/// resolve the sandboxed directories. `Flatpak` additionally exposes the host directories the sandbox hides, for
+/// example to migrate data a non-sandboxed build of the application wrote. The host directories are only reachable if
+/// the sandbox has been granted access to them, for example with `--filesystem=xdg-config`.
+///
+/// Returned by [`flatpak()`](crate::flatpak) and [`Resolver::flatpak()`].
+///
+/// # Examples
+///
+/// ```rust
+/// if let Some(flatpak) = dir_spec::flatpak() {
+///     println!("Running as {}", flatpak.app_id());
+///     if let Some(host) = flatpak.host_config_home() {
+///         println!("Host config directory: {}", host.display());
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Flatpak {
+  app_id: String,
+  resolver: Resolver,
+}
+
+impl Flatpak {
+  /// Detects a Flatpak sandbox from `$FLATPAK_ID`, falling back to the application name in `/.flatpak-info`.
+  ///
+  /// `/.flatpak-info` is read beneath the resolver's root, and only describes the current process otherwise, so it is
+  /// skipped when the resolver reads an injected environment without a root.
+  pub(crate) fn detect(resolver: &Resolver) -> Option<Self> {
+    if resolver.platform() != Platform::Unix {
+      return None;
+    }
+
+    let info_file = match resolver.root() {
+      Some(root) => Some(environment::beneath(Some(root), Path::new(INFO_FILE))),
+      None => resolver.uses_system_environment().then(|| PathBuf::from(INFO_FILE)),
+    };
+    let app_id = resolver
+      .environment()
+      .var_os(FLATPAK_ID)
+      .and_then(|id| id.into_string().ok())
+      .filter(|id| !id.is_empty())
+      .or_else(|| {
+        info_file
+          .and_then(|file| fs::read_to_string(file).ok())
+          .and_then(|info| parse_info(&info))
+      })?;
+    Some(Self {
+      app_id,
+      resolver: resolver.clone(),
+    })
+  }
+
+  /// Returns the application ID, such as `org.example.BarApp`.
+  pub fn app_id(&self) -> &str {
+    &self.app_id
+  }
+
+  /// Returns the read-only directory the application is installed in, `/app`.
+  ///
+  /// Data shipped with the application lives in `/app/share`, which Flatpak adds to `XDG_DATA_DIRS`.
+  pub fn app_prefix(&self) -> PathBuf {
+    PathBuf::from("/app")
+  }
+
+  /// Returns the sandboxed cache directory, `~/.var/app/<app id>/cache`.
+  pub fn cache_home(&self) -> Option<PathBuf> {
+    self.sandboxed(xdg::CACHE_HOME, "cache")
+  }
+
+  /// Returns the sandboxed configuration directory, `~/.var/app/<app id>/config`.
+  pub fn config_home(&self) -> Option<PathBuf> {
+    self.sandboxed(xdg::CONFIG_HOME, "config")
+  }
+
+  /// Returns the sandboxed data directory, `~/.var/app/<app id>/data`.
+  pub fn data_home(&self) -> Option<PathBuf> {
+    self.sandboxed(xdg::DATA_HOME, "data")
+  }
+
+  /// Returns the host's cache directory, from `$HOST_XDG_CACHE_HOME` or `~/.cache`.
+  pub fn host_cache_home(&self) -> Option<PathBuf> {
+    self.host(xdg::CACHE_HOME, ".cache")
+  }
+
+  /// Returns the host's configuration directory, from `$HOST_XDG_CONFIG_HOME` or `~/.config`.
+  pub fn host_config_home(&self) -> Option<PathBuf> {
+    self.host(xdg::CONFIG_HOME, ".config")
+  }
+
+  /// Returns the host's data directory, from `$HOST_XDG_DATA_HOME` or `~/.local/share`.
+  pub fn host_data_home(&self) -> Option<PathBuf> {
+    self.host(xdg::DATA_HOME, ".local/share")
+  }
+
+  /// Returns the host's state directory, from `$HOST_XDG_STATE_HOME` or `~/.local/state`.
+  pub fn host_state_home(&self) -> Option<PathBuf> {
+    self.host(xdg::STATE_HOME, ".local/state")
+  }
+
+  /// Returns the directory holding the application's sandboxed directories, `~/.var/app/<app id>`.
+  ///
+  /// The home directory is chosen by the resolver's [`HomeLookup`](crate::HomeLookup) and
+  /// [`SudoHome`](crate::SudoHome) policies, as for [`Resolver::home()`].
+  pub fn sandbox_dir(&self) -> Option<PathBuf> {
+    let platform = self.resolver.platform();
+    xdg::resolve_home(platform, &self.resolver.home_env(), ".var/app")
+      .into_path()
+      .map(|apps| platform.join(apps, &self.app_id))
+  }
+
+  /// Returns the sandboxed state directory, `~/.var/app/<app id>/.local/state`.
+  pub fn state_home(&self) -> Option<PathBuf> {
+    self.sandboxed(xdg::STATE_HOME, ".local/state")
+  }
+
+  /// Resolves the host directory Flatpak exports as `HOST_<key>`, or its XDG default.
+  fn host(&self, key: &str, relative: &str) -> Option<PathBuf> {
+    let platform = self.resolver.platform();
+    let env = self.resolver.home_env();
+    xdg::resolve_path(platform, &env, &format!("HOST_{key}"))
+      .or_else(|| xdg::resolve_home(platform, &env, relative))
+      .into_path()
+  }
+
+  fn sandboxed(&self, key: &str, relative: &str) -> Option<PathBuf> {
+    let platform = self.resolver.platform();
+    xdg::resolve_path(platform, self.resolver.environment(), key)
+      .into_path()
+      .or_else(|| self.sandbox_dir().map(|dir| platform.join(dir, relative)))
+  }
+}
+
+/// Returns the application name from the `[Application]` group of a `/.flatpak-info` key file.
+fn parse_info(contents: &str) -> Option<String> {
+  let mut in_application = false;
+  for line in contents.lines().map(str::trim) {
+    if line.starts_with('[') {
+      in_application = line == "[Application]";
+    } else if in_application
+      && let Some((key, value)) = line.split_once('=')
+      && key.trim() == "name"
+      && !value.trim().is_empty()
+    {
+      return Some(value.trim().to_string());
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{MapEnvironment, passwd::tests::image_root};
+
+  fn flatpak(env: MapEnvironment) -> Flatpak {
+    let resolver = Resolver::new()
+      .with_platform(Platform::Unix)
+      .with_environment(env.with_home("/home/alice").with_var(FLATPAK_ID, "org.example.BarApp"));
+    Flatpak::detect(&resolver).unwrap()
+  }
+
+  mod config_home {
+    use super::*;
+
+    #[test]
+    fn it_prefers_the_xdg_variable_flatpak_sets() {
+      let flatpak = flatpak(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "/sandbox/config"));
+
+      assert_eq!(flatpak.config_home(), Some(PathBuf::from("/sandbox/config")));
+    }
+
+    #[test]
+    fn it_uses_the_sandbox_directory() {
+      let flatpak = flatpak(MapEnvironment::new());
+
+      assert_eq!(
+        flatpak.config_home(),
+        Some(PathBuf::from("/home/alice/.var/app/org.example.BarApp/config"))
+      );
+    }
+  }
+
+  mod detect {
+    use super::*;
+
+    /// Writes an image root for `alice`, with `info` as its `.flatpak-info` if set.
+    fn image(info: Option<&str>) -> tempfile::TempDir {
+      let temp = image_root();
+      if let Some(info) = info {
+        fs::write(temp.path().join(".flatpak-info"), info).unwrap();
+      }
+      temp
+    }
+
+    #[test]
+    fn it_ignores_the_info_file_with_an_injected_environment() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new());
+
+      assert!(!resolver.uses_system_environment());
+      assert!(Flatpak::detect(&resolver).is_none());
+    }
+
+    #[test]
+    fn it_reads_flatpak_id() {
+      assert_eq!(flatpak(MapEnvironment::new()).app_id(), "org.example.BarApp");
+    }
+
+    #[test]
+    fn it_reads_the_info_file_beneath_the_root() {
+      let temp = image(Some("[Application]\nname=org.example.BarApp\n"));
+      let resolver = Resolver::for_user_in(temp.path(), "alice").unwrap();
+
+      assert_eq!(Flatpak::detect(&resolver).unwrap().app_id(), "org.example.BarApp");
+    }
+
+    #[test]
+    fn it_returns_none_without_an_info_file_beneath_the_root() {
+      let temp = image(None);
+      let resolver = Resolver::for_user_in(temp.path(), "alice").unwrap();
+
+      assert!(Flatpak::detect(&resolver).is_none());
+    }
+
+    #[test]
+    fn it_returns_none_on_other_platforms() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::MacOs)
+        .with_environment(MapEnvironment::new().with_var(FLATPAK_ID, "org.example.BarApp"));
+
+      assert!(Flatpak::detect(&resolver).is_none());
+    }
+  }
+
+  mod host_config_home {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_dot_config() {
+      let flatpak = flatpak(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "/sandbox/config"));
+
+      assert_eq!(flatpak.host_config_home(), Some(PathBuf::from("/home/alice/.config")));
+    }
+
+    #[test]
+    fn it_prefers_host_xdg_config_home() {
+      let flatpak = flatpak(MapEnvironment::new().with_var("HOST_XDG_CONFIG_HOME", "/test/config"));
+
+      assert_eq!(flatpak.host_config_home(), Some(PathBuf::from("/test/config")));
+    }
+  }
+
+  mod sandbox_dir {
+    use super::*;
+    use crate::SudoHome;
+
+    #[test]
+    fn it_follows_the_sudo_home_policy() {
+      let root = image_root();
+      let env = MapEnvironment::new()
+        .with_home("/root")
+        .with_var(FLATPAK_ID, "org.example.BarApp")
+        .with_var("SUDO_USER", "alice");
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(env)
+        .with_passwd_root(root.path())
+        .with_sudo_home(SudoHome::Invoking);
+
+      let flatpak = resolver.flatpak().unwrap();
+
+      assert_eq!(resolver.home(), Some(PathBuf::from("/home/alice")));
+      assert_eq!(
+        flatpak.sandbox_dir(),
+        Some(PathBuf::from("/home/alice/.var/app/org.example.BarApp"))
+      );
+      assert_eq!(flatpak.host_config_home(), Some(PathBuf::from("/home/alice/.config")));
+    }
+  }
+
+  mod parse_info {
+    use super::*;
+
+    #[test]
+    fn it_ignores_names_outside_the_application_group() {
+      let info = "[Runtime]\nname=org.freedesktop.Platform\n";
+
+      assert_eq!(parse_info(info), None);
+    }
+
+    #[test]
+    fn it_reads_the_application_name() {
+      let info = "[Application]\nname=org.example.BarApp\nruntime=runtime/org.freedesktop.Platform/x86_64/23.08\n";
+
+      assert_eq!(parse_info(info), Some("org.example.BarApp".to_string()));
+    }
+  }
+}
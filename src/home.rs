@@ -0,0 +1,367 @@
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  DirError, Environment, Explanation, Platform, Rejection, RejectionReason, SnapHome, Source, User,
+  passwd::{self, Entry},
+  snap, xdg,
+};
+
+const PKEXEC_UID: &str = "PKEXEC_UID";
+const SUDO_UID: &str = "SUDO_UID";
+const SUDO_USER: &str = "SUDO_USER";
+
+/// The [`Environment`] a [`Resolver`](crate::Resolver) resolves directories from, with the home directory chosen by
+/// its home policies.
+#[derive(Clone, Copy, Debug)]
+pub struct HomeEnvironment<'a> {
+  pub env: &'a dyn Environment,
+  pub home_lookup: HomeLookup,
+  pub passwd_root: Option<&'a Path>,
+  pub platform: Platform,
+  pub root: Option<&'a Path>,
+  pub snap_home: SnapHome,
+  pub sudo_home: SudoHome,
+  pub user: Option<&'a Entry>,
+}
+
+impl HomeEnvironment<'_> {
+  /// Explains which home directory the policies select.
+  pub fn explain_home(&self) -> Explanation {
+    if let Some(user) = self.user {
+      return passwd_home(user, None);
+    }
+
+    let lookup = || match self.home_lookup {
+      _ if self.platform == Platform::Windows => self.env_home(),
+      HomeLookup::Environment => self.env_home(),
+      HomeLookup::EnvironmentFirst => self.env_home().or_else(|| self.effective_home()),
+      HomeLookup::PasswdFirst => self.effective_home().or_else(|| self.env_home()),
+    };
+    let home = || match self.snap_home {
+      SnapHome::Real => xdg::resolve_path(self.platform, self.env, snap::REAL_HOME).or_else(lookup),
+      SnapHome::Rewritten => lookup(),
+    };
+
+    match self.sudo_home {
+      _ if self.platform == Platform::Windows => home(),
+      SudoHome::Effective => self.effective_home().or_else(home),
+      SudoHome::Environment => home(),
+      SudoHome::Invoking => self
+        .invoking_home(SUDO_UID)
+        .or_else(|| self.invoking_home(SUDO_USER))
+        .or_else(|| self.invoking_home(PKEXEC_UID))
+        .or_else(home),
+    }
+  }
+
+  /// Resolves the home directory of the effective user from the user database, or the passwd file beneath the passwd
+  /// root.
+  fn effective_home(&self) -> Explanation {
+    #[cfg(unix)]
+    if let Ok(entry) = passwd::lookup(self.passwd_root, &User::Uid(crate::secure::effective_uid())) {
+      return passwd_home(&entry, None);
+    }
+
+    Explanation::unresolved(DirError::MissingHome)
+  }
+
+  fn env_home(&self) -> Explanation {
+    self
+      .env
+      .home_dir()
+      .map_or(Explanation::unresolved(DirError::MissingHome), |home| {
+        Explanation::resolved(home, Source::home(""))
+      })
+  }
+
+  /// Resolves the home directory of the user named by `key`, which holds a uid unless it is `SUDO_USER`.
+  fn invoking_home(&self, key: &str) -> Explanation {
+    let missing = Explanation::unresolved(DirError::MissingHome);
+    let Some(value) = self.env.var_os(key) else {
+      return missing;
+    };
+
+    let user = value.to_str().and_then(|value| match key {
+      SUDO_USER => Some(User::from(value)),
+      _ => value.parse().ok().map(User::Uid),
+    });
+    match user.map(|user| passwd::lookup(self.passwd_root, &user)) {
+      Some(Ok(entry)) => passwd_home(&entry, Some(key)),
+      _ => missing.reject(Rejection::new(key, value, RejectionReason::UnknownUser)),
+    }
+  }
+}
+
+impl Environment for HomeEnvironment<'_> {
+  fn home_dir(&self) -> Option<PathBuf> {
+    self.explain_home().into_path()
+  }
+
+  fn root(&self) -> Option<&Path> {
+    self.root
+  }
+
+  fn var_os(&self, key: &str) -> Option<OsString> {
+    self.env.var_os(key)
+  }
+}
+
+/// Where a [`Resolver`](crate::Resolver) looks up the current user's home directory on Linux and macOS.
+///
+/// Minimal containers and some init systems start processes without `HOME`, leaving every directory derived from it
+/// unresolved. The passwd policies read the effective user's entry from the system's user database, which includes
+/// name services such as LDAP and macOS Directory Services, or from `etc/passwd` beneath the
+/// [passwd root](crate::Resolver::with_passwd_root) if one is set. [`explain_home()`](crate::Resolver::explain_home)
+/// reports whether the home directory came from the environment or from the passwd entry.
+///
+/// Windows has no passwd database, so the policy is ignored when resolving for Windows.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum HomeLookup {
+  /// Only the environment's home directory, such as `$HOME`.
+  #[default]
+  Environment,
+  /// The environment's home directory, falling back to the passwd entry when it is unset.
+  EnvironmentFirst,
+  /// The passwd entry, falling back to the environment's home directory when the effective user has no entry.
+  ///
+  /// Useful when `HOME` may be wrong rather than unset, as in some init contexts.
+  PasswdFirst,
+}
+
+/// Whose home directory a [`Resolver`](crate::Resolver) resolves directories from when the process runs through
+/// `sudo` or `pkexec`.
+///
+/// Depending on its configuration, `sudo` either keeps the invoking user's `HOME` or resets it to the target user's,
+/// so a tool running as root can end up writing root-owned files into the invoking user's `~/.config`, or reading
+/// root's configuration when it meant the user's. Choosing a policy makes the outcome independent of that
+/// configuration, and [`explain_home()`](crate::Resolver::explain_home) reports the passwd entry that was used.
+///
+/// Windows has no equivalent, so the policy is ignored when resolving for Windows.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SudoHome {
+  /// The home directory of the effective user, such as root under `sudo`, read from the user database regardless of
+  /// `HOME`.
+  ///
+  /// Falls back to the environment's home directory if the effective user has no passwd entry.
+  Effective,
+  /// The home directory from the environment, as `sudo` left it.
+  #[default]
+  Environment,
+  /// The home directory of the user who invoked `sudo` or `pkexec`, found in the user database from `$SUDO_UID`,
+  /// `$SUDO_USER` or `$PKEXEC_UID`.
+  ///
+  /// Falls back to the environment's home directory when none of them is set, as when the process was not elevated.
+  /// A variable naming an account without a passwd entry is reported as a [`Rejection`].
+  Invoking,
+}
+
+fn passwd_home(entry: &Entry, variable: Option<&str>) -> Explanation {
+  Explanation::resolved(
+    &entry.home,
+    Source::Passwd {
+      file: entry.file.clone(),
+      user: entry.name.clone(),
+      variable: variable.map(str::to_string),
+    },
+  )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use std::fs;
+
+  use tempfile::TempDir;
+
+  use super::*;
+  use crate::MapEnvironment;
+
+  fn home_env<'a>(env: &'a MapEnvironment, passwd_root: &'a Path) -> HomeEnvironment<'a> {
+    HomeEnvironment {
+      env,
+      home_lookup: HomeLookup::default(),
+      passwd_root: Some(passwd_root),
+      platform: Platform::Unix,
+      root: None,
+      snap_home: SnapHome::default(),
+      sudo_home: SudoHome::default(),
+      user: None,
+    }
+  }
+
+  /// Writes a passwd file listing the effective user as `alice` and uid 4242 as `bob`.
+  fn passwd_root() -> TempDir {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    fs::create_dir_all(root.join("etc")).unwrap();
+    let uid = crate::secure::effective_uid();
+    fs::write(
+      root.join("etc/passwd"),
+      format!("alice:x:{uid}:{uid}::/srv/alice:/bin/sh\nbob:x:4242:4242::/srv/bob:/bin/sh\n"),
+    )
+    .unwrap();
+    temp
+  }
+
+  mod explain_home {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_passwd_when_home_is_unset() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new();
+
+      let explanation = HomeEnvironment {
+        home_lookup: HomeLookup::EnvironmentFirst,
+        ..home_env(&env, root)
+      }
+      .explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/srv/alice")));
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::Passwd {
+          file: Some(root.join("etc/passwd")),
+          user: "alice".to_string(),
+          variable: None,
+        })
+      );
+    }
+
+    #[test]
+    fn it_falls_back_to_home_without_a_passwd_entry() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      fs::create_dir_all(root.join("etc")).unwrap();
+      fs::write(root.join("etc/passwd"), "carol:x:4343:4343::/srv/carol:/bin/sh\n").unwrap();
+      let env = MapEnvironment::new().with_home("/home/alice");
+      let empty = MapEnvironment::new();
+
+      let passwd_first = HomeEnvironment {
+        home_lookup: HomeLookup::PasswdFirst,
+        ..home_env(&env, root)
+      }
+      .explain_home();
+      let environment_first = HomeEnvironment {
+        home_lookup: HomeLookup::EnvironmentFirst,
+        ..home_env(&empty, root)
+      }
+      .explain_home();
+
+      assert_eq!(passwd_first.path(), Some(Path::new("/home/alice")));
+      assert_eq!(passwd_first.source(), Some(&Source::home("")));
+      assert_eq!(environment_first.into_result(), Err(DirError::MissingHome));
+    }
+
+    #[test]
+    fn it_queries_the_user_database_without_a_passwd_root() {
+      let env = MapEnvironment::new().with_home("/wrong/home");
+
+      let explanation = HomeEnvironment {
+        home_lookup: HomeLookup::PasswdFirst,
+        passwd_root: None,
+        ..home_env(&env, Path::new("/"))
+      }
+      .explain_home();
+
+      match passwd::lookup(None, &User::Uid(crate::secure::effective_uid())) {
+        Ok(entry) => {
+          assert_eq!(explanation.path(), Some(entry.home.as_path()));
+          assert!(matches!(
+            explanation.source(),
+            Some(Source::Passwd {
+              file: None,
+              ..
+            })
+          ));
+        }
+        Err(_) => assert_eq!(explanation.path(), Some(Path::new("/wrong/home"))),
+      }
+    }
+
+    #[test]
+    fn it_ignores_passwd_by_default() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new();
+
+      assert_eq!(
+        home_env(&env, root).explain_home().into_result(),
+        Err(DirError::MissingHome)
+      );
+    }
+
+    #[test]
+    fn it_ignores_sudo_variables_by_default() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new()
+        .with_home("/home/alice")
+        .with_var(SUDO_UID, "4242");
+
+      let explanation = home_env(&env, root).explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/home/alice")));
+    }
+
+    #[test]
+    fn it_prefers_passwd_over_home() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new().with_home("/wrong/home");
+
+      let explanation = HomeEnvironment {
+        home_lookup: HomeLookup::PasswdFirst,
+        ..home_env(&env, root)
+      }
+      .explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/srv/alice")));
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_invoking_user() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new().with_home("/root").with_var(SUDO_USER, "carol");
+
+      let explanation = HomeEnvironment {
+        sudo_home: SudoHome::Invoking,
+        ..home_env(&env, root)
+      }
+      .explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/root")));
+      assert_eq!(
+        explanation.rejected(),
+        [Rejection::new(SUDO_USER, "carol", RejectionReason::UnknownUser)]
+      );
+    }
+
+    #[test]
+    fn it_uses_the_invoking_user_from_sudo_uid() {
+      let temp = passwd_root();
+      let root = temp.path();
+      let env = MapEnvironment::new().with_home("/root").with_var(SUDO_UID, "4242");
+
+      let explanation = HomeEnvironment {
+        sudo_home: SudoHome::Invoking,
+        ..home_env(&env, root)
+      }
+      .explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/srv/bob")));
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::Passwd {
+          file: Some(root.join("etc/passwd")),
+          user: "bob".to_string(),
+          variable: Some(SUDO_UID.to_string()),
+        })
+      );
+    }
+  }
+}
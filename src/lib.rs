@@ -32,22 +32,49 @@
 //! }
 //! ```
 
-#[cfg(target_os = "macos")]
+mod app_dirs;
+mod candidate;
+mod ensure;
+mod environment;
+mod error;
+mod explain;
+mod flatpak;
+mod home;
 mod macos;
-#[cfg(all(target_family = "unix", not(target_os = "macos")))]
+mod migrate;
+mod passwd;
+mod platform;
+mod portable;
+mod resolver;
+mod secure;
+mod service;
+mod snap;
+pub mod system;
 mod unix;
-#[cfg(target_os = "windows")]
+mod user_dirs;
 mod windows;
 pub(crate) mod xdg;
 
-use std::{env, path::PathBuf};
-
-#[cfg(target_os = "macos")]
-use macos as os;
-#[cfg(all(target_family = "unix", not(target_os = "macos")))]
-use unix as os;
-#[cfg(target_os = "windows")]
-use windows as os;
+use std::{
+  env,
+  path::{Path, PathBuf},
+};
+
+pub use app_dirs::AppDirs;
+pub use candidate::Candidate;
+pub use ensure::EnsuredDir;
+pub use environment::{Environment, MapEnvironment, SystemEnvironment};
+pub use error::DirError;
+pub use explain::{Explanation, Rejection, RejectionReason, Source};
+pub use flatpak::Flatpak;
+pub use home::{HomeLookup, SudoHome};
+pub use migrate::{Layout, Migration, Move, Plan};
+pub use passwd::User;
+pub use platform::Platform;
+pub use portable::Portable;
+pub use resolver::{Convention, Resolver, RuntimeFallback};
+pub use snap::{Snap, SnapHome};
+pub use system::SystemDirs;
 
 /// Returns the user's binary directory.
 ///
@@ -64,7 +91,7 @@ use windows as os;
 /// }
 /// ```
 pub fn bin_home() -> Option<PathBuf> {
-  os::bin_home()
+  Resolver::new().bin_home()
 }
 
 /// Returns the user's cache directory.
@@ -83,7 +110,30 @@ pub fn bin_home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn cache_home() -> Option<PathBuf> {
-  os::cache_home()
+  Resolver::new().cache_home()
+}
+
+/// Returns the system configuration search directories.
+///
+/// Checks `XDG_CONFIG_DIRS` first, a list of directories separated by the platform path separator in
+/// order of preference. Relative entries are ignored, as required by the XDG spec. Falls back to platform
+/// defaults when the variable is unset, empty, or contains no absolute entries:
+/// - **Linux**: `/etc/xdg`
+/// - **macOS**: `/Library/Application Support`
+/// - **Windows**: `%PROGRAMDATA%`
+///
+/// The user-specific [`config_home()`] is **not** included in the returned list.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::config_dirs;
+/// for dir in config_dirs() {
+///     println!("Config search directory: {}", dir.display());
+/// }
+/// ```
+pub fn config_dirs() -> Vec<PathBuf> {
+  Resolver::new().config_dirs()
 }
 
 /// Returns the user's configuration directory.
@@ -102,7 +152,7 @@ pub fn cache_home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn config_home() -> Option<PathBuf> {
-  os::config_home()
+  Resolver::new().config_home()
 }
 
 /// Returns the user's local configuration directory (non-roaming).
@@ -124,7 +174,30 @@ pub fn config_home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn config_local() -> Option<PathBuf> {
-  os::config_local()
+  Resolver::new().config_local()
+}
+
+/// Returns the system data search directories.
+///
+/// Checks `XDG_DATA_DIRS` first, a list of directories separated by the platform path separator in
+/// order of preference. Relative entries are ignored, as required by the XDG spec. Falls back to platform
+/// defaults when the variable is unset, empty, or contains no absolute entries:
+/// - **Linux**: `/usr/local/share`, `/usr/share`
+/// - **macOS**: `/Library/Application Support`
+/// - **Windows**: `%PROGRAMDATA%`
+///
+/// The user-specific [`data_home()`] is **not** included in the returned list.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::data_dirs;
+/// for dir in data_dirs() {
+///     println!("Data search directory: {}", dir.display());
+/// }
+/// ```
+pub fn data_dirs() -> Vec<PathBuf> {
+  Resolver::new().data_dirs()
 }
 
 /// Returns the user's data directory.
@@ -143,7 +216,7 @@ pub fn config_local() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn data_home() -> Option<PathBuf> {
-  os::data_home()
+  Resolver::new().data_home()
 }
 
 /// Returns the user's local data directory (non-roaming).
@@ -165,12 +238,13 @@ pub fn data_home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn data_local() -> Option<PathBuf> {
-  os::data_local()
+  Resolver::new().data_local()
 }
 
 /// Returns the user's desktop directory.
 ///
-/// Checks `XDG_DESKTOP_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_DESKTOP_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Desktop`
 /// - **Windows**: `%USERPROFILE%\Desktop`
 ///
@@ -183,12 +257,13 @@ pub fn data_local() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn desktop() -> Option<PathBuf> {
-  os::desktop()
+  Resolver::new().desktop()
 }
 
 /// Returns the user's documents directory.
 ///
-/// Checks `XDG_DOCUMENTS_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_DOCUMENTS_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Documents`
 /// - **Windows**: `%USERPROFILE%\Documents`
 ///
@@ -201,12 +276,13 @@ pub fn desktop() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn documents() -> Option<PathBuf> {
-  os::documents()
+  Resolver::new().documents()
 }
 
 /// Returns the user's downloads directory.
 ///
-/// Checks `XDG_DOWNLOAD_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_DOWNLOAD_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Downloads`
 /// - **Windows**: `%USERPROFILE%\Downloads`
 ///
@@ -219,7 +295,173 @@ pub fn documents() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn downloads() -> Option<PathBuf> {
-  os::downloads()
+  Resolver::new().downloads()
+}
+
+/// Returns the user's [cache directory](crate::cache_home), creating it if it does not exist.
+///
+/// See [`ensure_config_home()`] for how directories are created and checked.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use dir_spec::ensure_cache_home;
+/// if let Ok(dir) = ensure_cache_home() {
+///     println!("Cache directory: {}", dir.path().display());
+/// }
+/// ```
+pub fn ensure_cache_home() -> Result<EnsuredDir, DirError> {
+  Resolver::new().ensure_cache_home()
+}
+
+/// Returns the user's [configuration directory](crate::config_home), creating it if it does not exist.
+///
+/// Missing directories are created with mode `0700`, as the XDG Base Directory Specification requires. Every existing
+/// component of the path must be owned by the current user or by root; a directory planted by another user is
+/// reported as [`DirError::Insecure`] rather than written to. Existing directories keep their permissions.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use dir_spec::ensure_config_home;
+/// match ensure_config_home() {
+///     Ok(dir) if dir.created() => println!("Created {}", dir.path().display()),
+///     Ok(dir) => println!("Using {}", dir.path().display()),
+///     Err(error) => eprintln!("{error}"),
+/// }
+/// ```
+pub fn ensure_config_home() -> Result<EnsuredDir, DirError> {
+  Resolver::new().ensure_config_home()
+}
+
+/// Returns the user's [data directory](crate::data_home), creating it if it does not exist.
+///
+/// See [`ensure_config_home()`] for how directories are created and checked.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use dir_spec::ensure_data_home;
+/// if let Ok(dir) = ensure_data_home() {
+///     println!("Data directory: {}", dir.path().display());
+/// }
+/// ```
+pub fn ensure_data_home() -> Result<EnsuredDir, DirError> {
+  Resolver::new().ensure_data_home()
+}
+
+/// Returns the user's [state directory](crate::state_home), creating it if it does not exist.
+///
+/// See [`ensure_config_home()`] for how directories are created and checked.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use dir_spec::ensure_state_home;
+/// if let Ok(dir) = ensure_state_home() {
+///     println!("State directory: {}", dir.path().display());
+/// }
+/// ```
+pub fn ensure_state_home() -> Result<EnsuredDir, DirError> {
+  Resolver::new().ensure_state_home()
+}
+
+/// Returns the first existing match for `relative` in the configuration search path.
+///
+/// Searches [`config_home()`] followed by each entry of [`config_dirs()`] in order of preference, as
+/// described by the XDG Base Directory Specification, and returns the first location where
+/// `relative` exists (either as a file or a directory). Returns `None` if there is no match or if
+/// `relative` could name something outside the search path, such as an absolute path or one containing `..`.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::find_config_file;
+/// if let Some(config) = find_config_file("myapp/config.toml") {
+///     println!("Using config file: {}", config.display());
+/// }
+/// ```
+pub fn find_config_file(relative: impl AsRef<Path>) -> Option<PathBuf> {
+  find_config_files(relative).into_iter().next()
+}
+
+/// Returns every existing match for `relative` in the configuration search path.
+///
+/// Searches [`config_home()`] followed by each entry of [`config_dirs()`] and returns all locations
+/// where `relative` exists, most important first. This is useful for merging configuration from
+/// several layers. Returns an empty list if `relative` could name something outside the search path, such as
+/// an absolute path or one containing `..`.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::find_config_files;
+/// for config in find_config_files("myapp/config.toml").iter().rev() {
+///     println!("Merging config file: {}", config.display());
+/// }
+/// ```
+pub fn find_config_files(relative: impl AsRef<Path>) -> Vec<PathBuf> {
+  xdg::find_files(
+    Platform::current(),
+    None,
+    config_home(),
+    config_dirs(),
+    relative.as_ref(),
+  )
+}
+
+/// Returns the first existing match for `relative` in the data search path.
+///
+/// Searches [`data_home()`] followed by each entry of [`data_dirs()`] in order of preference, as
+/// described by the XDG Base Directory Specification, and returns the first location where
+/// `relative` exists (either as a file or a directory). Returns `None` if there is no match or if
+/// `relative` could name something outside the search path, such as an absolute path or one containing `..`.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::find_data_file;
+/// if let Some(icon) = find_data_file("icons/hicolor/index.theme") {
+///     println!("Found icon theme: {}", icon.display());
+/// }
+/// ```
+pub fn find_data_file(relative: impl AsRef<Path>) -> Option<PathBuf> {
+  find_data_files(relative).into_iter().next()
+}
+
+/// Returns every existing match for `relative` in the data search path.
+///
+/// Searches [`data_home()`] followed by each entry of [`data_dirs()`] and returns all locations
+/// where `relative` exists, most important first. Returns an empty list if `relative` could name
+/// something outside the search path, such as an absolute path or one containing `..`.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::find_data_files;
+/// for dir in find_data_files("applications") {
+///     println!("Applications directory: {}", dir.display());
+/// }
+/// ```
+pub fn find_data_files(relative: impl AsRef<Path>) -> Vec<PathBuf> {
+  xdg::find_files(Platform::current(), None, data_home(), data_dirs(), relative.as_ref())
+}
+
+/// Detects whether the process runs in a Flatpak sandbox.
+///
+/// Returns the sandbox when `FLATPAK_ID` is set or `/.flatpak-info` names the application, and `None` otherwise or on
+/// macOS and Windows. See [`Flatpak`] for the sandboxed and host directories.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::flatpak;
+/// if let Some(flatpak) = flatpak() {
+///     println!("Flatpak app: {}", flatpak.app_id());
+/// }
+/// ```
+pub fn flatpak() -> Option<Flatpak> {
+  Resolver::new().flatpak()
 }
 
 /// Returns the user's fonts directory.
@@ -243,7 +485,7 @@ pub fn downloads() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn fonts() -> Option<PathBuf> {
-  os::fonts()
+  Resolver::new().fonts()
 }
 
 /// Returns the user's home directory.
@@ -262,9 +504,31 @@ pub fn home() -> Option<PathBuf> {
   env::home_dir()
 }
 
+/// Returns the user's log directory.
+///
+/// Checks `XDG_STATE_HOME` first, as the XDG spec treats logs as state, then falls back to platform defaults:
+/// - **Linux**: `~/.local/state`
+/// - **macOS**: `~/Library/Logs`
+/// - **Windows**: `%LOCALAPPDATA%`
+///
+/// Applications conventionally log to a directory of their own beneath it; [`AppDirs::log()`] returns it.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::logs;
+/// if let Some(logs) = logs() {
+///     println!("Log directory: {}", logs.display());
+/// }
+/// ```
+pub fn logs() -> Option<PathBuf> {
+  Resolver::new().logs()
+}
+
 /// Returns the user's music directory.
 ///
-/// Checks `XDG_MUSIC_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_MUSIC_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Music`
 /// - **Windows**: `%USERPROFILE%\Music`
 ///
@@ -277,12 +541,13 @@ pub fn home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn music() -> Option<PathBuf> {
-  os::music()
+  Resolver::new().music()
 }
 
 /// Returns the user's pictures directory.
 ///
-/// Checks `XDG_PICTURES_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_PICTURES_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Pictures`
 /// - **Windows**: `%USERPROFILE%\Pictures`
 ///
@@ -295,7 +560,7 @@ pub fn music() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn pictures() -> Option<PathBuf> {
-  os::pictures()
+  Resolver::new().pictures()
 }
 
 /// Returns the user's preferences directory.
@@ -317,12 +582,13 @@ pub fn pictures() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn preferences() -> Option<PathBuf> {
-  os::preferences()
+  Resolver::new().preferences()
 }
 
 /// Returns the user's public share directory.
 ///
-/// Checks `XDG_PUBLICSHARE_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_PUBLICSHARE_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Public`
 /// - **Windows**: `C:\Users\Public` (system-wide public folder)
 ///
@@ -335,7 +601,7 @@ pub fn preferences() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn publicshare() -> Option<PathBuf> {
-  os::publicshare()
+  Resolver::new().publicshare()
 }
 
 /// Returns the user's runtime directory.
@@ -354,7 +620,46 @@ pub fn publicshare() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn runtime() -> Option<PathBuf> {
-  os::runtime()
+  Resolver::new().runtime()
+}
+
+/// Returns the user's runtime directory if it is safe to place sockets and lock files in.
+///
+/// Unlike [`runtime()`], only `XDG_RUNTIME_DIR` is considered, and only when it names a directory (not a symbolic
+/// link) that is owned by the current user and has mode `0700`, as the XDG Base Directory Specification requires.
+/// There is no fallback to a shared directory such as `/tmp`. Use
+/// [`Resolver::explain_secure_runtime()`] or [`Resolver::try_secure_runtime()`] to find out why a directory was
+/// rejected.
+///
+/// Always returns `None` on platforms other than Unix.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::secure_runtime;
+/// if let Some(runtime) = secure_runtime() {
+///     println!("Socket: {}", runtime.join("app.sock").display());
+/// }
+/// ```
+pub fn secure_runtime() -> Option<PathBuf> {
+  Resolver::new().secure_runtime()
+}
+
+/// Detects whether the process runs in a snap.
+///
+/// Returns the snap when `SNAP_NAME` is set, and `None` otherwise or on macOS and Windows. See [`Snap`] for the real
+/// home directory and the directories snapd provides.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::snap;
+/// if let Some(snap) = snap() {
+///     println!("Snap: {}", snap.name());
+/// }
+/// ```
+pub fn snap() -> Option<Snap> {
+  Resolver::new().snap()
 }
 
 /// Returns the user's state directory.
@@ -373,12 +678,13 @@ pub fn runtime() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn state_home() -> Option<PathBuf> {
-  os::state_home()
+  Resolver::new().state_home()
 }
 
 /// Returns the user's templates directory.
 ///
-/// Checks `XDG_TEMPLATES_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_TEMPLATES_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux/macOS**: `~/Templates`
 /// - **Windows**: `%USERPROFILE%\Templates`
 ///
@@ -391,12 +697,13 @@ pub fn state_home() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn templates() -> Option<PathBuf> {
-  os::templates()
+  Resolver::new().templates()
 }
 
 /// Returns the user's videos directory.
 ///
-/// Checks `XDG_VIDEOS_DIR` first, then falls back to platform defaults:
+/// Checks `XDG_VIDEOS_DIR` first. On Linux, `$XDG_CONFIG_HOME/user-dirs.dirs` and the system `user-dirs.defaults`
+/// are consulted next, following `xdg-user-dirs`. Otherwise falls back to platform defaults:
 /// - **Linux**: `~/Videos`
 /// - **macOS**: `~/Movies` (following macOS convention)
 /// - **Windows**: `%USERPROFILE%\Videos`
@@ -410,7 +717,7 @@ pub fn templates() -> Option<PathBuf> {
 /// }
 /// ```
 pub fn videos() -> Option<PathBuf> {
-  os::videos()
+  Resolver::new().videos()
 }
 
 /// Returns the XDG binary directory, or a custom default.
@@ -429,7 +736,9 @@ pub fn videos() -> Option<PathBuf> {
 /// println!("Bin directory: {}", bin_dir.display());
 /// ```
 pub fn xdg_bin_home_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::BIN_HOME).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::BIN_HOME)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG cache directory, or a custom default.
@@ -448,7 +757,9 @@ pub fn xdg_bin_home_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Cache directory: {}", cache_dir.display());
 /// ```
 pub fn xdg_cache_home_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::CACHE_HOME).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::CACHE_HOME)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG configuration directory, or a custom default.
@@ -467,7 +778,9 @@ pub fn xdg_cache_home_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Config directory: {}", config_dir.display());
 /// ```
 pub fn xdg_config_home_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::CONFIG_HOME).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::CONFIG_HOME)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG data directory, or a custom default.
@@ -486,7 +799,9 @@ pub fn xdg_config_home_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Data directory: {}", data_dir.display());
 /// ```
 pub fn xdg_data_home_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::DATA_HOME).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::DATA_HOME)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG desktop directory, or a custom default.
@@ -505,7 +820,9 @@ pub fn xdg_data_home_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Desktop directory: {}", desktop_dir.display());
 /// ```
 pub fn xdg_desktop_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::DESKTOP_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::DESKTOP_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG documents directory, or a custom default.
@@ -524,7 +841,9 @@ pub fn xdg_desktop_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Documents directory: {}", docs_dir.display());
 /// ```
 pub fn xdg_documents_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::DOCUMENTS_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::DOCUMENTS_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG download directory, or a custom default.
@@ -543,7 +862,9 @@ pub fn xdg_documents_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Download directory: {}", dl_dir.display());
 /// ```
 pub fn xdg_download_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::DOWNLOAD_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::DOWNLOAD_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG music directory, or a custom default.
@@ -562,7 +883,9 @@ pub fn xdg_download_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Music directory: {}", music_dir.display());
 /// ```
 pub fn xdg_music_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::MUSIC_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::MUSIC_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG pictures directory, or a custom default.
@@ -581,7 +904,9 @@ pub fn xdg_music_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Pictures directory: {}", pics_dir.display());
 /// ```
 pub fn xdg_pictures_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::PICTURES_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::PICTURES_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG public share directory, or a custom default.
@@ -600,7 +925,9 @@ pub fn xdg_pictures_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Public directory: {}", public_dir.display());
 /// ```
 pub fn xdg_publicshare_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::PUBLICSHARE_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::PUBLICSHARE_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG runtime directory, or a custom default.
@@ -619,7 +946,9 @@ pub fn xdg_publicshare_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Runtime directory: {}", runtime_dir.display());
 /// ```
 pub fn xdg_runtime_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::RUNTIME_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::RUNTIME_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG state directory, or a custom default.
@@ -638,7 +967,9 @@ pub fn xdg_runtime_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("State directory: {}", state_dir.display());
 /// ```
 pub fn xdg_state_home_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::STATE_HOME).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::STATE_HOME)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG templates directory, or a custom default.
@@ -657,7 +988,9 @@ pub fn xdg_state_home_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Templates directory: {}", templates_dir.display());
 /// ```
 pub fn xdg_templates_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::TEMPLATES_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::TEMPLATES_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 /// Returns the XDG videos directory, or a custom default.
@@ -676,13 +1009,36 @@ pub fn xdg_templates_dir_or(default: impl Into<PathBuf>) -> PathBuf {
 /// println!("Videos directory: {}", videos_dir.display());
 /// ```
 pub fn xdg_videos_dir_or(default: impl Into<PathBuf>) -> PathBuf {
-  xdg::resolve_path(xdg::VIDEOS_DIR).unwrap_or_else(|| default.into())
+  xdg::resolve_path(Platform::current(), &SystemEnvironment, xdg::VIDEOS_DIR)
+    .into_path()
+    .unwrap_or_else(|| default.into())
 }
 
 #[cfg(test)]
 mod tests {
+  use std::fs;
+
   use super::*;
 
+  /// Runs `f` with `key` unset and the `user-dirs` files hidden, so the host's localized or disabled user
+  /// directories cannot change the platform default.
+  fn without_user_dirs(key: &str, f: impl FnOnce()) {
+    let empty = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+      [
+        (key, None),
+        ("XDG_CONFIG_DIRS", Some(empty.path().as_os_str())),
+        ("XDG_CONFIG_HOME", Some(empty.path().as_os_str())),
+      ],
+      f,
+    );
+  }
+
+  fn touch(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+  }
+
   mod bin_home {
     use temp_env::{with_var, with_var_unset};
 
@@ -777,6 +1133,58 @@ mod tests {
     }
   }
 
+  mod config_dirs {
+    use temp_env::{with_var, with_var_unset};
+
+    use super::*;
+
+    #[test]
+    fn respects_xdg_config_dirs() {
+      let paths = if cfg!(windows) {
+        vec![PathBuf::from("C:\\test\\one"), PathBuf::from("C:\\test\\two")]
+      } else {
+        vec![PathBuf::from("/test/one"), PathBuf::from("/test/two")]
+      };
+      let value = env::join_paths(&paths).unwrap();
+      with_var("XDG_CONFIG_DIRS", Some(value), || {
+        let result = config_dirs();
+        assert_eq!(result, paths);
+      });
+    }
+
+    #[test]
+    fn ignores_relative_entries() {
+      let absolute = if cfg!(windows) { "C:\\test\\one" } else { "/test/one" };
+      let value = env::join_paths([absolute, "relative/two"]).unwrap();
+      with_var("XDG_CONFIG_DIRS", Some(value), || {
+        let result = config_dirs();
+        assert_eq!(result, vec![PathBuf::from(absolute)]);
+      });
+    }
+
+    #[test]
+    fn uses_platform_default_when_xdg_unset() {
+      with_var_unset("XDG_CONFIG_DIRS", || {
+        let result = config_dirs();
+        assert!(result.iter().all(|p| p.is_absolute()));
+
+        #[cfg(all(target_family = "unix", not(target_os = "macos")))]
+        assert_eq!(result, vec![PathBuf::from("/etc/xdg")]);
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(result, vec![PathBuf::from("/Library/Application Support")]);
+      });
+    }
+
+    #[test]
+    fn uses_platform_default_when_only_relative_entries() {
+      with_var("XDG_CONFIG_DIRS", Some("relative/one"), || {
+        let result = config_dirs();
+        with_var_unset("XDG_CONFIG_DIRS", || assert_eq!(result, config_dirs()));
+      });
+    }
+  }
+
   mod config_home {
     use temp_env::{with_var, with_var_unset};
 
@@ -863,6 +1271,61 @@ mod tests {
     }
   }
 
+  mod data_dirs {
+    use temp_env::{with_var, with_var_unset};
+
+    use super::*;
+
+    #[test]
+    fn respects_xdg_data_dirs() {
+      let paths = if cfg!(windows) {
+        vec![PathBuf::from("C:\\test\\one"), PathBuf::from("C:\\test\\two")]
+      } else {
+        vec![PathBuf::from("/test/one"), PathBuf::from("/test/two")]
+      };
+      let value = env::join_paths(&paths).unwrap();
+      with_var("XDG_DATA_DIRS", Some(value), || {
+        let result = data_dirs();
+        assert_eq!(result, paths);
+      });
+    }
+
+    #[test]
+    fn ignores_relative_entries() {
+      let absolute = if cfg!(windows) { "C:\\test\\one" } else { "/test/one" };
+      let value = env::join_paths([absolute, "relative/two"]).unwrap();
+      with_var("XDG_DATA_DIRS", Some(value), || {
+        let result = data_dirs();
+        assert_eq!(result, vec![PathBuf::from(absolute)]);
+      });
+    }
+
+    #[test]
+    fn uses_platform_default_when_xdg_unset() {
+      with_var_unset("XDG_DATA_DIRS", || {
+        let result = data_dirs();
+        assert!(result.iter().all(|p| p.is_absolute()));
+
+        #[cfg(all(target_family = "unix", not(target_os = "macos")))]
+        assert_eq!(
+          result,
+          vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]
+        );
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(result, vec![PathBuf::from("/Library/Application Support")]);
+      });
+    }
+
+    #[test]
+    fn uses_platform_default_when_only_relative_entries() {
+      with_var("XDG_DATA_DIRS", Some("relative/one"), || {
+        let result = data_dirs();
+        with_var_unset("XDG_DATA_DIRS", || assert_eq!(result, data_dirs()));
+      });
+    }
+  }
+
   mod data_home {
     use temp_env::{with_var, with_var_unset};
 
@@ -946,7 +1409,7 @@ mod tests {
   }
 
   mod desktop {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -975,7 +1438,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_DESKTOP_DIR", || {
+      without_user_dirs("XDG_DESKTOP_DIR", || {
         let result = desktop();
         if let Some(desktop_path) = result {
           assert!(desktop_path.is_absolute());
@@ -986,7 +1449,7 @@ mod tests {
   }
 
   mod documents {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1015,7 +1478,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_DOCUMENTS_DIR", || {
+      without_user_dirs("XDG_DOCUMENTS_DIR", || {
         let result = documents();
         if let Some(documents_path) = result {
           assert!(documents_path.is_absolute());
@@ -1026,7 +1489,7 @@ mod tests {
   }
 
   mod downloads {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::{with_var, with_vars};
 
     use super::*;
 
@@ -1055,7 +1518,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_DOWNLOAD_DIR", || {
+      without_user_dirs("XDG_DOWNLOAD_DIR", || {
         let result = downloads();
         if let Some(downloads_path) = result {
           assert!(downloads_path.is_absolute());
@@ -1063,6 +1526,190 @@ mod tests {
         }
       });
     }
+
+    #[test]
+    #[cfg(all(target_family = "unix", not(target_os = "macos")))]
+    fn reads_user_dirs_file_when_xdg_unset() {
+      let temp = tempfile::tempdir().unwrap();
+      let config = temp.path();
+      fs::write(
+        config.join("user-dirs.dirs"),
+        "XDG_DOWNLOAD_DIR=\"$HOME/Téléchargements\"\n",
+      )
+      .unwrap();
+
+      with_vars(
+        [
+          ("XDG_CONFIG_HOME", Some(config.as_os_str())),
+          ("XDG_DOWNLOAD_DIR", None),
+          ("HOME", Some("/home/user".as_ref())),
+        ],
+        || {
+          let result = downloads();
+          assert_eq!(result, Some(PathBuf::from("/home/user/Téléchargements")));
+        },
+      );
+    }
+  }
+
+  mod find_config_file {
+    use temp_env::with_vars;
+
+    use super::*;
+
+    #[test]
+    fn prefers_config_home_over_config_dirs() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let home = root.join("home");
+      let system = root.join("system");
+      touch(&home.join("app/config.toml"));
+      touch(&system.join("app/config.toml"));
+
+      with_vars(
+        [
+          ("XDG_CONFIG_HOME", Some(home.as_os_str())),
+          ("XDG_CONFIG_DIRS", Some(system.as_os_str())),
+        ],
+        || {
+          let result = find_config_file("app/config.toml");
+          assert_eq!(result, Some(home.join("app/config.toml")));
+        },
+      );
+    }
+
+    #[test]
+    fn falls_through_to_config_dirs() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let home = root.join("home");
+      let first = root.join("first");
+      let second = root.join("second");
+      touch(&second.join("app/config.toml"));
+      let dirs = env::join_paths([&first, &second]).unwrap();
+
+      with_vars(
+        [
+          ("XDG_CONFIG_HOME", Some(home.as_os_str())),
+          ("XDG_CONFIG_DIRS", Some(dirs.as_os_str())),
+        ],
+        || {
+          let result = find_config_file("app/config.toml");
+          assert_eq!(result, Some(second.join("app/config.toml")));
+        },
+      );
+    }
+
+    #[test]
+    fn returns_none_for_absolute_path() {
+      let result = find_config_file(env::temp_dir());
+      assert_eq!(result, None);
+    }
+
+    #[test]
+    fn returns_none_for_parent_dir_segments() {
+      let temp = tempfile::tempdir().unwrap();
+      let home = temp.path().join("home");
+      touch(&temp.path().join("secret"));
+      fs::create_dir_all(&home).unwrap();
+
+      with_vars([("XDG_CONFIG_HOME", Some(home.as_os_str()))], || {
+        assert_eq!(find_config_file("../secret"), None);
+        assert_eq!(find_config_file("app/../../secret"), None);
+      });
+    }
+  }
+
+  mod find_config_files {
+    use temp_env::with_vars;
+
+    use super::*;
+
+    #[test]
+    fn returns_all_matches_in_precedence_order() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let home = root.join("home");
+      let first = root.join("first");
+      let second = root.join("second");
+      let third = root.join("third");
+      touch(&home.join("app/config.toml"));
+      touch(&first.join("app/config.toml"));
+      touch(&third.join("app/config.toml"));
+      let dirs = env::join_paths([&first, &second, &third]).unwrap();
+
+      with_vars(
+        [
+          ("XDG_CONFIG_HOME", Some(home.as_os_str())),
+          ("XDG_CONFIG_DIRS", Some(dirs.as_os_str())),
+        ],
+        || {
+          let result = find_config_files("app/config.toml");
+          assert_eq!(
+            result,
+            vec![
+              home.join("app/config.toml"),
+              first.join("app/config.toml"),
+              third.join("app/config.toml"),
+            ]
+          );
+        },
+      );
+    }
+  }
+
+  mod find_data_file {
+    use temp_env::with_vars;
+
+    use super::*;
+
+    #[test]
+    fn prefers_data_home_over_data_dirs() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let home = root.join("home");
+      let system = root.join("system");
+      touch(&home.join("app/data.db"));
+      touch(&system.join("app/data.db"));
+
+      with_vars(
+        [
+          ("XDG_DATA_HOME", Some(home.as_os_str())),
+          ("XDG_DATA_DIRS", Some(system.as_os_str())),
+        ],
+        || {
+          let result = find_data_file("app/data.db");
+          assert_eq!(result, Some(home.join("app/data.db")));
+        },
+      );
+    }
+  }
+
+  mod find_data_files {
+    use temp_env::with_vars;
+
+    use super::*;
+
+    #[test]
+    fn returns_all_matches_in_precedence_order() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let home = root.join("home");
+      let system = root.join("system");
+      touch(&home.join("app/data.db"));
+      touch(&system.join("app/data.db"));
+
+      with_vars(
+        [
+          ("XDG_DATA_HOME", Some(home.as_os_str())),
+          ("XDG_DATA_DIRS", Some(system.as_os_str())),
+        ],
+        || {
+          let result = find_data_files("app/data.db");
+          assert_eq!(result, vec![home.join("app/data.db"), system.join("app/data.db")]);
+        },
+      );
+    }
   }
 
   mod fonts {
@@ -1130,7 +1777,7 @@ mod tests {
   }
 
   mod music {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1159,7 +1806,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_MUSIC_DIR", || {
+      without_user_dirs("XDG_MUSIC_DIR", || {
         let result = music();
         if let Some(music_path) = result {
           assert!(music_path.is_absolute());
@@ -1170,7 +1817,7 @@ mod tests {
   }
 
   mod pictures {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1199,7 +1846,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_PICTURES_DIR", || {
+      without_user_dirs("XDG_PICTURES_DIR", || {
         let result = pictures();
         if let Some(pictures_path) = result {
           assert!(pictures_path.is_absolute());
@@ -1249,7 +1896,7 @@ mod tests {
   }
 
   mod publicshare {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1278,7 +1925,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_PUBLICSHARE_DIR", || {
+      without_user_dirs("XDG_PUBLICSHARE_DIR", || {
         let result = publicshare();
         if let Some(public_path) = result {
           assert!(public_path.is_absolute());
@@ -1296,7 +1943,7 @@ mod tests {
     fn uses_system_public_on_windows() {
       #[cfg(target_os = "windows")]
       {
-        with_var_unset("XDG_PUBLICSHARE_DIR", || {
+        without_user_dirs("XDG_PUBLICSHARE_DIR", || {
           let result = publicshare();
           assert_eq!(result, Some(PathBuf::from("C:\\Users\\Public")));
         });
@@ -1422,7 +2069,7 @@ mod tests {
   }
 
   mod templates {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1451,7 +2098,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_TEMPLATES_DIR", || {
+      without_user_dirs("XDG_TEMPLATES_DIR", || {
         let result = templates();
         if let Some(templates_path) = result {
           assert!(templates_path.is_absolute());
@@ -1462,7 +2109,7 @@ mod tests {
   }
 
   mod videos {
-    use temp_env::{with_var, with_var_unset};
+    use temp_env::with_var;
 
     use super::*;
 
@@ -1491,7 +2138,7 @@ mod tests {
 
     #[test]
     fn uses_platform_default_when_xdg_unset() {
-      with_var_unset("XDG_VIDEOS_DIR", || {
+      without_user_dirs("XDG_VIDEOS_DIR", || {
         let result = videos();
         if let Some(videos_path) = result {
           assert!(videos_path.is_absolute());
@@ -1,82 +1,97 @@
-use std::{env, path::PathBuf};
+use std::path::PathBuf;
 
-use crate::xdg;
+use crate::{
+  Environment, Platform,
+  explain::{Explanation, Source},
+  xdg,
+};
 
 const APP_SUPPORT: &str = "Library/Application Support";
+const PLATFORM: Platform = Platform::MacOs;
+const SYSTEM_APP_SUPPORT: &str = "/Library/Application Support";
 
-pub fn bin_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::BIN_HOME, ".local/bin")
+pub fn bin_home(env: &dyn Environment) -> Explanation {
+  xdg::bin_home(PLATFORM, env)
 }
 
-pub fn cache_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::CACHE_HOME, "Library/Caches")
+pub fn cache_home(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::CACHE_HOME, "Library/Caches")
 }
 
-pub fn config_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::CONFIG_HOME, APP_SUPPORT)
+pub fn config_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::CONFIG_DIRS).unwrap_or_else(|| vec![PathBuf::from(SYSTEM_APP_SUPPORT)])
 }
 
-pub fn config_local() -> Option<PathBuf> {
-  config_home()
+pub fn config_home(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::CONFIG_HOME, APP_SUPPORT)
 }
 
-pub fn data_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DATA_HOME, APP_SUPPORT)
+pub fn config_local(env: &dyn Environment) -> Explanation {
+  config_home(env)
 }
 
-pub fn data_local() -> Option<PathBuf> {
-  data_home()
+pub fn data_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::DATA_DIRS).unwrap_or_else(|| vec![PathBuf::from(SYSTEM_APP_SUPPORT)])
 }
 
-pub fn desktop() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DESKTOP_DIR, "Desktop")
+pub fn data_home(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::DATA_HOME, APP_SUPPORT)
 }
 
-pub fn documents() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DOCUMENTS_DIR, "Documents")
+pub fn data_local(env: &dyn Environment) -> Explanation {
+  data_home(env)
 }
 
-pub fn downloads() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DOWNLOAD_DIR, "Downloads")
+pub fn desktop(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::DESKTOP_DIR, "Desktop")
 }
 
-pub fn fonts() -> Option<PathBuf> {
-  env::home_dir().map(|p| p.join("Library/Fonts"))
+pub fn documents(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::DOCUMENTS_DIR, "Documents")
 }
 
-pub fn music() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::MUSIC_DIR, "Music")
+pub fn downloads(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::DOWNLOAD_DIR, "Downloads")
 }
 
-pub fn pictures() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::PICTURES_DIR, "Pictures")
+pub fn fonts(env: &dyn Environment) -> Explanation {
+  xdg::resolve_home(PLATFORM, env, "Library/Fonts")
 }
 
-pub fn preferences() -> Option<PathBuf> {
-  env::home_dir().map(|p| p.join("Library/Preferences"))
+pub fn logs(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::STATE_HOME, "Library/Logs")
 }
 
-pub fn publicshare() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::PUBLICSHARE_DIR, "Public")
+pub fn music(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::MUSIC_DIR, "Music")
 }
 
-pub fn runtime() -> Option<PathBuf> {
-  xdg::resolve_path(xdg::RUNTIME_DIR).or_else(|| {
-    env::var("TMPDIR")
-      .ok()
-      .map(PathBuf::from)
-      .or_else(|| Some(PathBuf::from("/tmp")))
-  })
+pub fn pictures(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::PICTURES_DIR, "Pictures")
 }
 
-pub fn state_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::STATE_HOME, APP_SUPPORT)
+pub fn preferences(env: &dyn Environment) -> Explanation {
+  xdg::resolve_home(PLATFORM, env, "Library/Preferences")
 }
 
-pub fn templates() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::TEMPLATES_DIR, "Templates")
+pub fn publicshare(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::PUBLICSHARE_DIR, "Public")
 }
 
-pub fn videos() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::VIDEOS_DIR, "Movies")
+pub fn runtime(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, xdg::RUNTIME_DIR)
+    .or_else(|| Explanation::var(PLATFORM, env, "TMPDIR", ""))
+    .or_else(|| Explanation::resolved("/tmp", Source::Default))
+}
+
+pub fn state_home(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::STATE_HOME, APP_SUPPORT)
+}
+
+pub fn templates(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::TEMPLATES_DIR, "Templates")
+}
+
+pub fn videos(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path_with_fallback(PLATFORM, env, xdg::VIDEOS_DIR, "Movies")
 }
@@ -0,0 +1,509 @@
+use std::{
+  ffi::{OsStr, OsString},
+  fmt::{self, Display, Formatter},
+  fs,
+  io::{self, ErrorKind},
+  path::{Path, PathBuf},
+};
+
+use crate::{DirError, ensure};
+
+/// Moves an application's files from a legacy dotfile directory, such as `~/.bar-app`, into its XDG directories.
+///
+/// Each rule maps entries of the legacy directory whose names match a pattern to a target directory. Patterns match
+/// names directly inside the legacy directory and support `*` (any run of characters) and `?` (any single character);
+/// a matching directory is moved as a whole. The first matching rule wins, and entries no rule matches stay where they
+/// are.
+///
+/// [`plan()`](Self::plan) reports what would happen without touching the file system, so tools can show a dry run
+/// before calling [`Plan::execute()`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use dir_spec::{AppDirs, Migration};
+///
+/// let app = AppDirs::new("com", "Foo Corp", "Bar App");
+/// let (Some(home), Some(config), Some(state)) = (dir_spec::home(), app.config(), app.state()) else {
+///     return;
+/// };
+/// let migration = Migration::new(home.join(".bar-app"))
+///   .with_rule("config.toml", &config)
+///   .with_rule("*.log", state)
+///   .with_symlink(config);
+///
+/// if let Ok(plan) = migration.plan() {
+///     print!("{plan}");
+///     if plan.conflicts().is_empty() {
+///         plan.execute().expect("migration failed");
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migration {
+  legacy: PathBuf,
+  rules: Vec<(String, PathBuf)>,
+  symlink: Option<PathBuf>,
+}
+
+impl Migration {
+  /// Creates a migration out of the legacy directory `legacy`, with no rules.
+  pub fn new(legacy: impl Into<PathBuf>) -> Self {
+    Self {
+      legacy: legacy.into(),
+      rules: Vec::new(),
+      symlink: None,
+    }
+  }
+
+  /// Detects which layout the application's files are currently in.
+  pub fn layout(&self) -> Layout {
+    let legacy = self
+      .entries()
+      .is_ok_and(|entries| entries.iter().any(|(name, _)| self.target(name).is_some()));
+    let migrated = self.rules.iter().any(|(_, target)| target.exists());
+
+    match (legacy, migrated) {
+      (true, true) => Layout::Both,
+      (true, false) => Layout::Legacy,
+      (false, true) => Layout::Migrated,
+      (false, false) => Layout::Fresh,
+    }
+  }
+
+  /// Returns the legacy directory.
+  pub fn legacy(&self) -> &Path {
+    &self.legacy
+  }
+
+  /// Plans the migration without changing the file system.
+  ///
+  /// A legacy directory that does not exist, or that is already a symbolic link, yields a plan with nothing to move.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Migrate`] if the legacy directory exists but cannot be read.
+  pub fn plan(&self) -> Result<Plan, DirError> {
+    let mut plan = Plan {
+      conflicts: Vec::new(),
+      layout: self.layout(),
+      legacy: self.legacy.clone(),
+      moves: Vec::new(),
+      remaining: Vec::new(),
+      symlink: None,
+    };
+
+    for (name, from) in self.entries()? {
+      let Some(target) = self.target(&name) else {
+        plan.remaining.push(from);
+        continue;
+      };
+
+      let entry = Move {
+        from,
+        to: target.join(name),
+      };
+      if fs::symlink_metadata(&entry.to).is_ok() {
+        plan.conflicts.push(entry);
+      } else {
+        plan.moves.push(entry);
+      }
+    }
+
+    if plan.remaining.is_empty() && !plan.moves.is_empty() {
+      plan.symlink.clone_from(&self.symlink);
+    }
+    Ok(plan)
+  }
+
+  /// Adds a rule moving entries whose names match `pattern` into `target`.
+  pub fn with_rule(mut self, pattern: impl Into<String>, target: impl Into<PathBuf>) -> Self {
+    self.rules.push((pattern.into(), target.into()));
+    self
+  }
+
+  /// Replaces the legacy directory with a symbolic link to `target` once every entry has been moved, so that older
+  /// versions of the application and scripts keep finding their files.
+  ///
+  /// No link is created while entries that no rule matches remain in the legacy directory.
+  pub fn with_symlink(mut self, target: impl Into<PathBuf>) -> Self {
+    self.symlink = Some(target.into());
+    self
+  }
+
+  /// Lists the legacy directory's entries sorted by name, or none if it is missing or a symbolic link.
+  fn entries(&self) -> Result<Vec<(OsString, PathBuf)>, DirError> {
+    match fs::symlink_metadata(&self.legacy) {
+      Ok(metadata) if metadata.is_dir() => {}
+      _ => return Ok(Vec::new()),
+    }
+
+    let error = |error: io::Error| DirError::Migrate {
+      kind: error.kind(),
+      path: self.legacy.clone(),
+    };
+    let mut entries = fs::read_dir(&self.legacy)
+      .map_err(error)?
+      .map(|entry| entry.map(|entry| (entry.file_name(), entry.path())))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(error)?;
+    entries.sort();
+    Ok(entries)
+  }
+
+  fn target(&self, name: &OsStr) -> Option<&Path> {
+    let name = name.to_string_lossy();
+    self
+      .rules
+      .iter()
+      .find(|(pattern, _)| matches(pattern.as_bytes(), name.as_bytes()))
+      .map(|(_, target)| target.as_path())
+  }
+}
+
+/// Which layout an application's files are in, as detected by [`Migration::layout()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Layout {
+  /// The legacy directory has entries to move, and at least one target directory already exists.
+  ///
+  /// The application may have been run in both layouts, so its files can be split between them.
+  Both,
+  /// Neither the legacy directory nor any target directory has been created yet.
+  Fresh,
+  /// Only the legacy directory has entries to move.
+  Legacy,
+  /// The legacy directory has nothing left to move, and at least one target directory exists.
+  Migrated,
+}
+
+/// A single entry moved by a [`Plan`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Move {
+  from: PathBuf,
+  to: PathBuf,
+}
+
+impl Move {
+  /// Returns the entry's path in the legacy directory.
+  pub fn from(&self) -> &Path {
+    &self.from
+  }
+
+  /// Returns the entry's path in its target directory.
+  pub fn to(&self) -> &Path {
+    &self.to
+  }
+}
+
+/// What a [`Migration`] would do, returned by [`Migration::plan()`].
+///
+/// Displaying a plan lists each step on its own line, for use as a dry run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Plan {
+  conflicts: Vec<Move>,
+  layout: Layout,
+  legacy: PathBuf,
+  moves: Vec<Move>,
+  remaining: Vec<PathBuf>,
+  symlink: Option<PathBuf>,
+}
+
+impl Plan {
+  /// Returns the entries whose destination already exists. [`execute()`](Self::execute) refuses to run while there
+  /// are any.
+  pub fn conflicts(&self) -> &[Move] {
+    &self.conflicts
+  }
+
+  /// Carries out the plan.
+  ///
+  /// Each entry is renamed into place, which is atomic when the legacy and target directories share a file system.
+  /// Across file systems the entry is copied next to its destination under a temporary name, renamed into place, and
+  /// only then deleted from the legacy directory. Missing target directories are created as described in
+  /// [`ensure_config_home()`](crate::ensure_config_home).
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Migrate`] with [`ErrorKind::AlreadyExists`] if the plan has conflicts or a destination
+  /// appeared since it was made, and [`DirError::Migrate`] or [`DirError::Create`] if an entry cannot be moved. Entries
+  /// moved before the error stay moved.
+  pub fn execute(&self) -> Result<(), DirError> {
+    if let Some(conflict) = self.conflicts.first() {
+      return Err(DirError::Migrate {
+        kind: ErrorKind::AlreadyExists,
+        path: conflict.to.clone(),
+      });
+    }
+
+    for entry in &self.moves {
+      if let Some(parent) = entry.to.parent() {
+        ensure::dir(parent.to_path_buf(), None)?;
+      }
+      relocate(&entry.from, &entry.to).map_err(|error| DirError::Migrate {
+        kind: error.kind(),
+        path: entry.from.clone(),
+      })?;
+    }
+
+    if let Some(target) = &self.symlink {
+      fs::remove_dir(&self.legacy)
+        .and_then(|()| symlink(target, &self.legacy))
+        .map_err(|error| DirError::Migrate {
+          kind: error.kind(),
+          path: self.legacy.clone(),
+        })?;
+    }
+    Ok(())
+  }
+
+  /// Returns `true` if the plan has nothing to move.
+  pub fn is_empty(&self) -> bool {
+    self.moves.is_empty()
+  }
+
+  /// Returns the layout detected when the plan was made.
+  pub fn layout(&self) -> Layout {
+    self.layout
+  }
+
+  /// Returns the entries that will be moved, sorted by their path in the legacy directory.
+  pub fn moves(&self) -> &[Move] {
+    &self.moves
+  }
+
+  /// Returns the legacy entries no rule matches, which stay in place.
+  pub fn remaining(&self) -> &[PathBuf] {
+    &self.remaining
+  }
+
+  /// Returns where the legacy directory will link to once the entries have been moved, if anywhere.
+  pub fn symlink(&self) -> Option<&Path> {
+    self.symlink.as_deref()
+  }
+}
+
+impl Display for Plan {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    for entry in &self.moves {
+      writeln!(f, "move `{}` to `{}`", entry.from.display(), entry.to.display())?;
+    }
+    for entry in &self.conflicts {
+      writeln!(
+        f,
+        "skip `{}`: `{}` already exists",
+        entry.from.display(),
+        entry.to.display()
+      )?;
+    }
+    for path in &self.remaining {
+      writeln!(f, "keep `{}`", path.display())?;
+    }
+    if let Some(target) = &self.symlink {
+      writeln!(f, "link `{}` to `{}`", self.legacy.display(), target.display())?;
+    }
+    Ok(())
+  }
+}
+
+/// Copies `from` to `to`, recreating directories and symbolic links.
+fn copy(from: &Path, to: &Path) -> io::Result<()> {
+  let metadata = fs::symlink_metadata(from)?;
+  if metadata.is_symlink() {
+    symlink(&fs::read_link(from)?, to)
+  } else if metadata.is_dir() {
+    fs::create_dir(to)?;
+    fs::set_permissions(to, metadata.permissions())?;
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      copy(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+  } else {
+    fs::copy(from, to).map(|_| ())
+  }
+}
+
+/// Returns whether `name` matches `pattern`, where `*` matches any run of bytes and `?` any single byte.
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+  match (pattern.split_first(), name.split_first()) {
+    (None, None) => true,
+    (Some((b'*', rest)), _) => matches(rest, name) || (!name.is_empty() && matches(pattern, &name[1..])),
+    (Some((b'?', rest)), Some((_, name))) => matches(rest, name),
+    (Some((expected, rest)), Some((actual, name))) => expected == actual && matches(rest, name),
+    _ => false,
+  }
+}
+
+/// Moves `from` to `to`, falling back to copying and deleting when they are on different file systems.
+fn relocate(from: &Path, to: &Path) -> io::Result<()> {
+  if fs::symlink_metadata(to).is_ok() {
+    return Err(ErrorKind::AlreadyExists.into());
+  }
+
+  match fs::rename(from, to) {
+    Err(error) if error.kind() == ErrorKind::CrossesDevices => {}
+    result => return result,
+  }
+
+  let mut name = OsString::from(".");
+  name.push(to.file_name().unwrap_or_default());
+  name.push(".migrating");
+  let staging = to.with_file_name(name);
+
+  copy(from, &staging)
+    .and_then(|()| fs::rename(&staging, to))
+    .inspect_err(|_| {
+      let _ = fs::remove_dir_all(&staging).or_else(|_| fs::remove_file(&staging));
+    })?;
+  if fs::symlink_metadata(from)?.is_dir() {
+    fs::remove_dir_all(from)
+  } else {
+    fs::remove_file(from)
+  }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+  std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+  if fs::metadata(target).is_ok_and(|metadata| metadata.is_dir()) {
+    std::os::windows::fs::symlink_dir(target, link)
+  } else {
+    std::os::windows::fs::symlink_file(target, link)
+  }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+  Err(ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(unix)]
+  mod execute {
+    use super::*;
+
+    #[test]
+    fn it_moves_entries_and_links_the_legacy_directory() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let legacy = root.join(".bar-app");
+      let config = root.join("config/bar-app");
+      fs::create_dir_all(legacy.join("themes")).unwrap();
+      fs::write(legacy.join("config.toml"), "theme = 'dark'").unwrap();
+      fs::write(legacy.join("themes/dark.toml"), "").unwrap();
+      let migration = Migration::new(&legacy).with_rule("*", &config).with_symlink(&config);
+
+      migration.plan().unwrap().execute().unwrap();
+
+      assert_eq!(
+        fs::read_to_string(config.join("config.toml")).unwrap(),
+        "theme = 'dark'"
+      );
+      assert!(config.join("themes/dark.toml").is_file());
+      assert_eq!(fs::read_link(&legacy).unwrap(), config);
+      assert_eq!(migration.layout(), Layout::Migrated);
+    }
+  }
+
+  mod matches {
+    use super::*;
+
+    #[test]
+    fn it_matches_wildcards() {
+      assert!(matches(b"*.log", b"error.log"));
+      assert!(matches(b"history?", b"history2"));
+      assert!(matches(b"*", b""));
+      assert!(!matches(b"*.log", b"error.log.1"));
+      assert!(!matches(b"history?", b"history"));
+    }
+  }
+
+  #[cfg(unix)]
+  mod plan {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Writes a legacy directory and returns it with the config and state targets beneath the same temp directory.
+    fn legacy() -> (TempDir, PathBuf, PathBuf, PathBuf) {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let legacy = root.join(".bar-app");
+      fs::create_dir_all(&legacy).unwrap();
+      for file in ["config.toml", "error.log", "access.log"] {
+        fs::write(legacy.join(file), file).unwrap();
+      }
+      let (config, state) = (root.join("config/bar-app"), root.join("state/bar-app"));
+      (temp, legacy, config, state)
+    }
+
+    #[test]
+    fn it_does_not_touch_the_file_system() {
+      let (_temp, legacy, config, state) = legacy();
+
+      let plan = Migration::new(&legacy)
+        .with_rule("config.toml", &config)
+        .with_rule("*.log", &state)
+        .with_symlink(&config)
+        .plan()
+        .unwrap();
+
+      assert_eq!(plan.layout(), Layout::Legacy);
+      assert_eq!(
+        plan.moves().iter().map(Move::to).collect::<Vec<_>>(),
+        [
+          state.join("access.log"),
+          config.join("config.toml"),
+          state.join("error.log")
+        ]
+      );
+      assert_eq!(plan.symlink(), Some(config.as_path()));
+      assert!(legacy.join("config.toml").is_file());
+      assert!(!config.exists());
+    }
+
+    #[test]
+    fn it_keeps_unmatched_entries_and_skips_the_symlink() {
+      let (_temp, legacy, config, _) = legacy();
+
+      let plan = Migration::new(&legacy)
+        .with_rule("config.toml", &config)
+        .with_symlink(&config)
+        .plan()
+        .unwrap();
+
+      assert_eq!(plan.remaining(), [legacy.join("access.log"), legacy.join("error.log")]);
+      assert_eq!(plan.symlink(), None);
+    }
+
+    #[test]
+    fn it_reports_conflicts() {
+      let (_temp, legacy, config, _) = legacy();
+      fs::create_dir_all(&config).unwrap();
+      fs::write(config.join("config.toml"), "new").unwrap();
+
+      let plan = Migration::new(&legacy)
+        .with_rule("config.toml", &config)
+        .plan()
+        .unwrap();
+
+      assert_eq!(plan.layout(), Layout::Both);
+      assert!(plan.is_empty());
+      assert_eq!(plan.conflicts()[0].to(), config.join("config.toml"));
+      assert_eq!(
+        plan.execute(),
+        Err(DirError::Migrate {
+          kind: ErrorKind::AlreadyExists,
+          path: config.join("config.toml"),
+        })
+      );
+    }
+  }
+}
@@ -0,0 +1,292 @@
+use std::{
+  fmt::{self, Display, Formatter},
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::DirError;
+
+const PASSWD: &str = "etc/passwd";
+
+/// A user account, identified by name or by user id.
+///
+/// Accounts on the running system are looked up with `getpwnam_r` and `getpwuid_r`, so accounts from other name
+/// services, such as LDAP or macOS Directory Services, are found as well. Accounts beneath a root, such as an unpacked
+/// container image, are read from its `etc/passwd` file.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum User {
+  /// An account name, such as `alice`.
+  Name(String),
+  /// A numeric user id, such as `1000`.
+  Uid(u32),
+}
+
+impl Display for User {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Name(name) => write!(f, "`{name}`"),
+      Self::Uid(uid) => write!(f, "uid {uid}"),
+    }
+  }
+}
+
+impl From<&str> for User {
+  fn from(name: &str) -> Self {
+    Self::Name(name.to_string())
+  }
+}
+
+impl From<String> for User {
+  fn from(name: String) -> Self {
+    Self::Name(name)
+  }
+}
+
+impl From<u32> for User {
+  fn from(uid: u32) -> Self {
+    Self::Uid(uid)
+  }
+}
+
+/// An account read from the system's user database or from a passwd file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+  /// The passwd file the account was read from, or `None` if it came from the system's user database.
+  pub file: Option<PathBuf>,
+  pub home: PathBuf,
+  pub name: String,
+  pub uid: u32,
+}
+
+/// Finds `user` in the `etc/passwd` file beneath `root`, or in the system's user database without a root.
+pub fn lookup(root: Option<&Path>, user: &User) -> Result<Entry, DirError> {
+  let unknown = || DirError::UnknownUser {
+    user: user.clone(),
+  };
+
+  #[cfg(any(
+    target_vendor = "apple",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+  ))]
+  if root.is_none() {
+    let (name, uid, home) = sys::lookup(user).ok_or_else(unknown)?;
+    return Ok(Entry {
+      file: None,
+      home,
+      name,
+      uid,
+    });
+  }
+
+  let file = root.unwrap_or(Path::new("/")).join(PASSWD);
+  let contents = fs::read_to_string(&file).map_err(|_| unknown())?;
+  let (name, uid, home) = contents
+    .lines()
+    .filter_map(parse_line)
+    .find(|(name, uid, _)| match user {
+      User::Name(wanted) => name == wanted,
+      User::Uid(wanted) => uid == wanted,
+    })
+    .ok_or_else(unknown)?;
+  Ok(Entry {
+    file: Some(file),
+    home,
+    name,
+    uid,
+  })
+}
+
+/// Parses a `name:password:uid:gid:gecos:home:shell` line, skipping comments, NIS entries and empty home directories.
+fn parse_line(line: &str) -> Option<(String, u32, PathBuf)> {
+  if line.starts_with(['#', '+', '-']) {
+    return None;
+  }
+
+  let fields: Vec<&str> = line.split(':').collect();
+  let (name, uid, home) = (fields.first()?, fields.get(2)?, fields.get(5)?);
+  if name.is_empty() || home.is_empty() {
+    return None;
+  }
+  Some(((*name).to_string(), uid.parse().ok()?, PathBuf::from(home)))
+}
+
+/// Looks up accounts with `getpwnam_r` and `getpwuid_r`, which consult every name service the system is configured
+/// with.
+#[cfg(any(
+  target_vendor = "apple",
+  target_os = "linux",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+  target_os = "dragonfly"
+))]
+#[allow(unsafe_code)]
+mod sys {
+  use std::{
+    ffi::{CStr, CString, OsStr, c_char},
+    mem::MaybeUninit,
+    os::unix::ffi::OsStrExt,
+    path::PathBuf,
+    ptr,
+  };
+
+  use super::User;
+
+  const MAX_BUFFER: usize = 1 << 20;
+
+  /// Returns the name, uid and home directory of `user`, skipping accounts without a home directory.
+  pub fn lookup(user: &User) -> Option<(String, u32, PathBuf)> {
+    let name = match user {
+      User::Name(name) => CString::new(name.as_str()).ok()?,
+      User::Uid(_) => CString::default(),
+    };
+
+    let mut buffer: Vec<c_char> = vec![0; 1024];
+    loop {
+      let mut entry = MaybeUninit::<libc::passwd>::uninit();
+      let mut result = ptr::null_mut();
+      // SAFETY: every pointer refers to memory that outlives the call, and `size` is the length of `buffer`
+      let code = unsafe {
+        match user {
+          User::Name(_) => libc::getpwnam_r(
+            name.as_ptr(),
+            entry.as_mut_ptr(),
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &raw mut result,
+          ),
+          User::Uid(uid) => libc::getpwuid_r(
+            *uid,
+            entry.as_mut_ptr(),
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &raw mut result,
+          ),
+        }
+      };
+
+      if code == libc::ERANGE && buffer.len() < MAX_BUFFER {
+        buffer.resize(buffer.len() * 2, 0);
+        continue;
+      }
+      if code != 0 || result.is_null() {
+        return None;
+      }
+
+      // SAFETY: on success `result` points to `entry`, whose strings live in `buffer`
+      let entry = unsafe { &*result };
+      if entry.pw_name.is_null() || entry.pw_dir.is_null() {
+        return None;
+      }
+      // SAFETY: both pointers were checked for null and point to NUL-terminated strings in `buffer`
+      let (account, home) = unsafe { (CStr::from_ptr(entry.pw_name), CStr::from_ptr(entry.pw_dir)) };
+      if home.is_empty() {
+        return None;
+      }
+      return Some((
+        account.to_string_lossy().into_owned(),
+        entry.pw_uid,
+        PathBuf::from(OsStr::from_bytes(home.to_bytes())),
+      ));
+    }
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use tempfile::TempDir;
+
+  use super::*;
+
+  /// Writes an image root whose `etc/passwd` holds `alice`, with uid 1000 and home `/home/alice`.
+  pub(crate) fn image_root() -> TempDir {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("etc")).unwrap();
+    fs::write(
+      root.path().join(PASSWD),
+      "alice:x:1000:1000::/home/alice:/bin/sh
+",
+    )
+    .unwrap();
+    root
+  }
+
+  mod lookup {
+    use super::*;
+
+    fn root() -> TempDir {
+      let root = tempfile::tempdir().unwrap();
+      fs::create_dir_all(root.path().join("etc")).unwrap();
+      fs::write(
+        root.path().join(PASSWD),
+        "root:x:0:0:root:/root:/bin/sh\n# comment\nalice:x:1000:1000:Alice,,,:/home/alice:/bin/bash\n",
+      )
+      .unwrap();
+      root
+    }
+
+    #[test]
+    fn it_finds_a_user_by_name() {
+      let root = root();
+
+      let entry = lookup(Some(root.path()), &User::from("alice")).unwrap();
+
+      assert_eq!(entry.home, PathBuf::from("/home/alice"));
+      assert_eq!(entry.uid, 1000);
+      assert_eq!(entry.file, Some(root.path().join("etc/passwd")));
+    }
+
+    #[test]
+    fn it_finds_a_user_by_uid() {
+      let root = root();
+
+      let entry = lookup(Some(root.path()), &User::Uid(0)).unwrap();
+
+      assert_eq!(entry.name, "root");
+      assert_eq!(entry.home, PathBuf::from("/root"));
+    }
+
+    #[cfg(any(target_vendor = "apple", target_os = "linux"))]
+    #[test]
+    fn it_queries_the_user_database_without_a_root() {
+      let entry = lookup(None, &User::Uid(0)).unwrap();
+
+      assert_eq!(entry.name, "root");
+      assert_eq!(entry.file, None);
+      assert_eq!(lookup(None, &User::from("root")), Ok(entry));
+    }
+
+    #[test]
+    fn it_reports_an_unknown_user() {
+      let root = root();
+
+      assert_eq!(
+        lookup(Some(root.path()), &User::from("bob")),
+        Err(DirError::UnknownUser {
+          user: User::from("bob"),
+        })
+      );
+      assert_eq!(
+        lookup(None, &User::from("dir-spec-no-such-user")),
+        Err(DirError::UnknownUser {
+          user: User::from("dir-spec-no-such-user"),
+        })
+      );
+    }
+  }
+
+  mod parse_line {
+    use super::*;
+
+    #[test]
+    fn it_skips_nis_entries_and_malformed_lines() {
+      assert_eq!(parse_line("+@admins::::::"), None);
+      assert_eq!(parse_line("alice:x:not-a-uid:1000::/home/alice:/bin/sh"), None);
+      assert_eq!(parse_line("alice:x:1000"), None);
+    }
+  }
+}
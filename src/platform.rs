@@ -0,0 +1,216 @@
+use std::{
+  ffi::{OsStr, OsString},
+  path::{Path, PathBuf},
+};
+
+/// The platform whose directory conventions a [`Resolver`](crate::Resolver) follows.
+///
+/// Every platform's rules are always compiled, so a program running on Linux can ask what
+/// `config_home()` would be on Windows given a particular [`Environment`](crate::Environment).
+/// Paths produced for a platform other than the host use that platform's path separator.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{MapEnvironment, Platform, Resolver};
+///
+/// let env = MapEnvironment::new().with_var("APPDATA", "C:\\Users\\alice\\AppData\\Roaming");
+/// let resolver = Resolver::new().with_platform(Platform::Windows).with_environment(env);
+///
+/// assert_eq!(resolver.config_home(), Some("C:\\Users\\alice\\AppData\\Roaming".into()));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Platform {
+  /// macOS, using `~/Library` locations.
+  MacOs,
+  /// Linux, the BSDs, and other Unix-like systems, using the XDG Base Directory defaults.
+  Unix,
+  /// Windows, using known folder environment variables such as `%APPDATA%`.
+  Windows,
+}
+
+impl Platform {
+  /// Returns the platform the crate was compiled for.
+  pub const fn current() -> Self {
+    if cfg!(target_os = "macos") {
+      Self::MacOs
+    } else if cfg!(target_os = "windows") {
+      Self::Windows
+    } else {
+      Self::Unix
+    }
+  }
+
+  /// Returns `true` if `path` is absolute under this platform's rules.
+  pub(crate) fn is_absolute(self, path: &Path) -> bool {
+    if self.is_host_compatible() {
+      return path.is_absolute();
+    }
+
+    let path = path.to_string_lossy();
+    match self {
+      Self::MacOs | Self::Unix => path.starts_with('/'),
+      Self::Windows => {
+        let bytes = path.as_bytes();
+        path.starts_with("\\\\")
+          || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+      }
+    }
+  }
+
+  /// Returns `true` if `value` can be interpreted under this platform's rules without loss.
+  ///
+  /// Values are only inspected as Unicode when resolving for a platform other than the host's.
+  pub(crate) fn is_representable(self, value: &OsStr) -> bool {
+    self.is_host_compatible() || value.to_str().is_some()
+  }
+
+  /// Joins `relative` onto `base` using this platform's path separator.
+  ///
+  /// `relative` may use `/` as a separator regardless of platform.
+  pub(crate) fn join(self, base: impl AsRef<Path>, relative: impl AsRef<Path>) -> PathBuf {
+    let (base, relative) = (base.as_ref(), relative.as_ref());
+    if self.is_host_compatible() {
+      return base.join(relative);
+    }
+
+    let separator = self.separator();
+    let relative = relative.to_string_lossy().replace(['/', '\\'], &separator.to_string());
+    let mut joined = OsString::from(base);
+    if !joined.to_string_lossy().ends_with(separator) {
+      joined.push(separator.to_string());
+    }
+    joined.push(relative.trim_start_matches(separator));
+    PathBuf::from(joined)
+  }
+
+  /// Splits a search path such as `XDG_DATA_DIRS` using this platform's list separator.
+  pub(crate) fn split_paths(self, value: &OsStr) -> Vec<PathBuf> {
+    if self.is_host_compatible() {
+      return std::env::split_paths(value).collect();
+    }
+
+    let separator = match self {
+      Self::MacOs | Self::Unix => ':',
+      Self::Windows => ';',
+    };
+    value.to_string_lossy().split(separator).map(PathBuf::from).collect()
+  }
+
+  fn is_host_compatible(self) -> bool {
+    match self {
+      Self::MacOs | Self::Unix => cfg!(unix),
+      Self::Windows => cfg!(windows),
+    }
+  }
+
+  fn separator(self) -> char {
+    match self {
+      Self::MacOs | Self::Unix => '/',
+      Self::Windows => '\\',
+    }
+  }
+}
+
+impl Default for Platform {
+  fn default() -> Self {
+    Self::current()
+  }
+}
+
+/// Calls the backend function for a [`Platform`].
+macro_rules! dispatch {
+  ($platform:expr, $function:ident($($arg:expr),* $(,)?)) => {
+    match $platform {
+      $crate::Platform::MacOs => $crate::macos::$function($($arg),*),
+      $crate::Platform::Unix => $crate::unix::$function($($arg),*),
+      $crate::Platform::Windows => $crate::windows::$function($($arg),*),
+    }
+  };
+}
+
+pub(crate) use dispatch;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod current {
+    use super::*;
+
+    #[test]
+    fn it_matches_target_os() {
+      #[cfg(target_os = "macos")]
+      assert_eq!(Platform::current(), Platform::MacOs);
+
+      #[cfg(target_os = "windows")]
+      assert_eq!(Platform::current(), Platform::Windows);
+
+      #[cfg(all(target_family = "unix", not(target_os = "macos")))]
+      assert_eq!(Platform::current(), Platform::Unix);
+    }
+  }
+
+  mod is_absolute {
+    use super::*;
+
+    #[test]
+    fn it_detects_unix_absolute_paths() {
+      assert!(Platform::Unix.is_absolute(Path::new("/home/alice")));
+      assert!(!Platform::Unix.is_absolute(Path::new("home/alice")));
+    }
+
+    #[test]
+    fn it_detects_windows_absolute_paths() {
+      assert!(Platform::Windows.is_absolute(Path::new("C:\\Users\\alice")));
+      assert!(Platform::Windows.is_absolute(Path::new("\\\\server\\share")));
+      assert!(!Platform::Windows.is_absolute(Path::new("Users\\alice")));
+      assert!(!Platform::Windows.is_absolute(Path::new("C:relative")));
+    }
+  }
+
+  mod join {
+    use super::*;
+
+    #[test]
+    fn it_joins_with_unix_separator() {
+      let joined = Platform::Unix.join("/home/alice", ".local/share");
+
+      assert_eq!(joined, PathBuf::from("/home/alice/.local/share"));
+    }
+
+    #[test]
+    fn it_joins_with_windows_separator() {
+      let joined = Platform::Windows.join("C:\\Users\\alice\\", "Foo Corp/Bar App");
+
+      assert_eq!(joined.to_string_lossy(), "C:\\Users\\alice\\Foo Corp\\Bar App");
+    }
+  }
+
+  mod split_paths {
+    use super::*;
+
+    #[test]
+    fn it_splits_unix_search_paths() {
+      let paths = Platform::Unix.split_paths(OsStr::new("/usr/local/share:/usr/share"));
+
+      assert_eq!(
+        paths,
+        vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]
+      );
+    }
+
+    #[test]
+    fn it_splits_windows_search_paths() {
+      let paths = Platform::Windows.split_paths(OsStr::new("C:\\ProgramData;D:\\Shared"));
+
+      assert_eq!(
+        paths,
+        vec![PathBuf::from("C:\\ProgramData"), PathBuf::from("D:\\Shared")]
+      );
+    }
+  }
+}
@@ -0,0 +1,137 @@
+use std::{env, path::PathBuf};
+
+use crate::Environment;
+
+const MARKER: &str = "portable.txt";
+
+/// When [`AppDirs`](crate::AppDirs) keeps an application's files next to its executable, for installs that travel on a
+/// USB stick or live in a single unpacked folder.
+///
+/// Portable mode is active when a marker file exists in the executable's directory, or when an environment variable
+/// is set to a non-empty value. While it is active, the application's configuration, data, cache and state
+/// directories are `config`, `data`, `cache` and `state` beneath the executable's directory. The runtime directory is
+/// unaffected, since removable drives often cannot hold sockets.
+///
+/// Pass the rule to [`AppDirs::with_portable()`](crate::AppDirs::with_portable).
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{AppDirs, Portable};
+///
+/// // Portable when portable.txt sits next to the executable or BAR_APP_PORTABLE is set
+/// let app = AppDirs::new("com", "Foo Corp", "Bar App").with_portable(Portable::new().with_var("BAR_APP_PORTABLE"));
+/// if let Some(dir) = app.portable_dir() {
+///     println!("Running in portable mode from {}", dir.display());
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Portable {
+  dir: Option<PathBuf>,
+  marker: Option<String>,
+  var: Option<String>,
+}
+
+impl Portable {
+  /// Detects portable mode from a `portable.txt` file in the executable's directory.
+  pub fn new() -> Self {
+    Self {
+      dir: None,
+      marker: Some(MARKER.to_string()),
+      var: None,
+    }
+  }
+
+  /// Keeps the application's files beneath `dir` instead of the executable's directory, and looks for the marker file
+  /// there.
+  pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.dir = Some(dir.into());
+    self
+  }
+
+  /// Detects portable mode from a file named `name` instead of `portable.txt`.
+  pub fn with_marker(mut self, name: impl Into<String>) -> Self {
+    self.marker = Some(name.into());
+    self
+  }
+
+  /// Also detects portable mode when the environment variable `key` is set to a non-empty value.
+  pub fn with_var(mut self, key: impl Into<String>) -> Self {
+    self.var = Some(key.into());
+    self
+  }
+
+  /// Stops detecting portable mode from a marker file, leaving only the [environment variable](Self::with_var).
+  pub fn without_marker(mut self) -> Self {
+    self.marker = None;
+    self
+  }
+
+  /// Returns the directory holding the application's files if portable mode is active.
+  pub(crate) fn detect(&self, env: &dyn Environment) -> Option<PathBuf> {
+    let dir = self
+      .dir
+      .clone()
+      .or_else(|| env::current_exe().ok()?.parent().map(PathBuf::from))?;
+
+    let by_var = self
+      .var
+      .as_deref()
+      .and_then(|key| env.var_os(key))
+      .is_some_and(|value| !value.is_empty());
+    let by_marker = self.marker.as_ref().is_some_and(|marker| dir.join(marker).is_file());
+    (by_var || by_marker).then_some(dir)
+  }
+}
+
+impl Default for Portable {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+  use crate::MapEnvironment;
+
+  mod detect {
+    use super::*;
+
+    #[test]
+    fn it_detects_the_marker_file() {
+      let temp = tempfile::tempdir().unwrap();
+      let dir = temp.path();
+      fs::write(dir.join(MARKER), "").unwrap();
+
+      let detected = Portable::new().with_dir(dir).detect(&MapEnvironment::new());
+
+      assert_eq!(detected, Some(dir.to_path_buf()));
+    }
+
+    #[test]
+    fn it_detects_the_variable() {
+      let temp = tempfile::tempdir().unwrap();
+      let dir = temp.path();
+      let env = MapEnvironment::new().with_var("BAR_APP_PORTABLE", "1");
+
+      let portable = Portable::new().with_dir(dir).without_marker();
+
+      assert_eq!(
+        portable.clone().with_var("BAR_APP_PORTABLE").detect(&env),
+        Some(dir.to_path_buf())
+      );
+      assert_eq!(portable.detect(&env), None);
+    }
+
+    #[test]
+    fn it_is_inactive_without_a_marker() {
+      let temp = tempfile::tempdir().unwrap();
+      let dir = temp.path();
+
+      assert_eq!(Portable::new().with_dir(dir).detect(&MapEnvironment::new()), None);
+    }
+  }
+}
@@ -0,0 +1,1715 @@
+use std::{
+  any::Any,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use crate::{
+  Candidate, DirError, EnsuredDir, Environment, Flatpak, HomeLookup, MapEnvironment, Platform, Snap, SnapHome,
+  SudoHome, SystemEnvironment, User, candidate, ensure, environment,
+  explain::Explanation,
+  home::HomeEnvironment,
+  passwd::{self, Entry},
+  platform::dispatch,
+  secure, service, xdg,
+};
+
+/// The naming convention used for platform fallbacks.
+///
+/// Under [`Native`](Self::Native) and [`Xdg`](Self::Xdg), XDG environment variables always take precedence and the
+/// convention only decides where directories resolve when those variables are unset.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Convention {
+  /// Use the platform's native locations (e.g. `~/Library/Application Support` on macOS).
+  #[default]
+  Native,
+  /// Resolve as a system service rather than for a user's session.
+  ///
+  /// The directories systemd creates for a unit's `StateDirectory=`, `CacheDirectory=`, `RuntimeDirectory=`,
+  /// `ConfigurationDirectory=` and `LogsDirectory=` settings are preferred, read from the `$STATE_DIRECTORY`,
+  /// `$CACHE_DIRECTORY`, `$RUNTIME_DIRECTORY`, `$CONFIGURATION_DIRECTORY` and `$LOGS_DIRECTORY` variables (the first
+  /// entry when a unit lists several). Otherwise directories fall back to the system-wide directories of
+  /// [`SystemDirs`](crate::SystemDirs): `/var/lib` for data and state, `/var/cache`, `/run`, `/etc` and `/var/log` on
+  /// Linux, their `/Library` equivalents on macOS, and `%PROGRAMDATA%` on Windows, which has no system runtime
+  /// directory. XDG variables and the home directory are not consulted, so services without a home directory still
+  /// resolve.
+  ///
+  /// Since systemd's variables name the unit's own directories, [`AppDirs`](crate::AppDirs) uses them as-is and only
+  /// appends the application name to the system locations.
+  ///
+  /// Directories without a service equivalent, such as [`bin_home()`](Resolver::bin_home), resolve as under
+  /// [`Native`](Self::Native).
+  Service,
+  /// Use the XDG Base Directory defaults (`~/.config`, `~/.cache`, `~/.local/share`, `~/.local/state`
+  /// and `~/.local/bin`) on every platform.
+  ///
+  /// This is the layout most command line tools expect, and lets them live in the same place on macOS
+  /// and Windows as on Linux without requiring users to export every `XDG_*` variable.
+  Xdg,
+}
+
+/// Where [`runtime()`](Resolver::runtime) resolves when `XDG_RUNTIME_DIR` is unset, as it often is under cron, ssh
+/// sessions without `pam_systemd`, and containers.
+///
+/// Windows always falls back to `%TEMP%`, which is already private to the user.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum RuntimeFallback {
+  /// A private directory named `<name>-runtime-<uid>` beneath `$TMPDIR` or `/tmp`, created with mode `0700` if it
+  /// does not exist.
+  ///
+  /// An existing path is only reused if it is a directory (not a symbolic link) owned by the user with mode `0700`, so
+  /// another user cannot plant a directory or link there to intercept sockets. If the directory cannot be created or
+  /// fails these checks, the runtime directory does not resolve. [`secure_runtime()`](Resolver::secure_runtime) also
+  /// uses this fallback.
+  ///
+  /// A resolver created with [`for_user()`](Resolver::for_user) uses that account's uid and only reuses its existing
+  /// directory, without creating one. A resolver with a [`root()`](Resolver::root), such as one created with
+  /// [`for_user_in()`](Resolver::for_user_in), never resolves this fallback.
+  Private(String),
+  /// The shared `$TMPDIR` or `/tmp` directory.
+  #[default]
+  Shared,
+}
+
+/// A configurable directory resolver.
+///
+/// The module-level functions such as [`config_home()`](crate::config_home) use the default resolver.
+/// A `Resolver` exposes the same functions as methods so that programs can opt into different
+/// resolution rules without affecting the rest of the process.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{Convention, Resolver};
+///
+/// // ~/.config on every platform unless XDG_CONFIG_HOME says otherwise
+/// let resolver = Resolver::new().with_convention(Convention::Xdg);
+/// if let Some(config) = resolver.config_home() {
+///     println!("Config directory: {}", config.display());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Resolver {
+  convention: Convention,
+  env: Arc<dyn Environment>,
+  home_lookup: HomeLookup,
+  passwd_root: Option<PathBuf>,
+  platform: Platform,
+  root: Option<PathBuf>,
+  runtime_fallback: RuntimeFallback,
+  snap_home: SnapHome,
+  sudo_home: SudoHome,
+  system_env: bool,
+  user: Option<Entry>,
+}
+
+impl Resolver {
+  /// Creates a resolver for another user's directories, such as when an administrative tool running as root manages
+  /// a user's configuration.
+  ///
+  /// The user's home directory is read from the system's user database, including name services such as LDAP and
+  /// macOS Directory Services, and [`explain_home()`](Self::explain_home) reports the entry it came from. The current
+  /// process's environment belongs to a different user, so the resolver starts with an empty environment and resolves
+  /// every directory from its platform fallback beneath that home directory. Pass the user's captured environment to
+  /// [`with_environment()`](Self::with_environment) to honor their `XDG_*` variables; the home directory from the
+  /// passwd entry is kept.
+  ///
+  /// [`secure_runtime()`](Self::secure_runtime) and the `ensure_*` methods check directory ownership against the user
+  /// rather than the current process's user.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::UnknownUser`] if the account is not in the user database, and [`DirError::Unsupported`] on
+  /// Windows.
+  ///
+  /// # Examples
+  ///
+  /// ```rust,no_run
+  /// use dir_spec::Resolver;
+  ///
+  /// let alice = Resolver::for_user("alice")?;
+  /// let config = alice.config_home(); // /home/alice/.config
+  /// # Ok::<(), dir_spec::DirError>(())
+  /// ```
+  pub fn for_user(user: impl Into<User>) -> Result<Self, DirError> {
+    let platform = Platform::current();
+    if platform == Platform::Windows {
+      return Err(DirError::Unsupported {
+        platform,
+      });
+    }
+
+    let user = passwd::lookup(None, &user.into())?;
+    Ok(Self {
+      env: Arc::new(MapEnvironment::new()),
+      system_env: false,
+      user: Some(user),
+      ..Self::new()
+    })
+  }
+
+  /// Creates a resolver for a user of the Linux file system rooted at `root`, such as an unpacked container image or
+  /// a chroot, when building or inspecting it from outside.
+  ///
+  /// The user's home directory is read from `etc/passwd` beneath `root`, and `user-dirs.dirs`, `user-dirs.conf` and
+  /// `user-dirs.defaults` are read beneath `root` as well, so the host's files never leak into the result. Resolved
+  /// directories are absolute paths as seen from inside the image, such as `/home/alice/.config`;
+  /// [`host_path()`](Self::host_path) maps them to where they live beneath `root`. As with
+  /// [`for_user()`](Self::for_user), the resolver starts with an empty environment. Nothing is created on the host:
+  /// the `ensure_*` methods return [`DirError::Unsupported`].
+  ///
+  /// Paths are mapped beneath `root` without touching the file system, so `..` never climbs above it, but symbolic
+  /// links inside the image are followed by the host. An absolute link such as `home/alice/.config -> /etc` makes
+  /// files be read from the host's `/etc`, so only resolve inside images whose links you trust.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::UnknownUser`] if the account is not in `etc/passwd` beneath `root`.
+  ///
+  /// # Examples
+  ///
+  /// ```rust,no_run
+  /// use dir_spec::Resolver;
+  ///
+  /// let alice = Resolver::for_user_in("/images/rootfs", "alice")?;
+  /// if let Some(config) = alice.config_home() {
+  ///     println!("{}", config.display()); // /home/alice/.config
+  ///     println!("{}", alice.host_path(&config).display()); // /images/rootfs/home/alice/.config
+  /// }
+  /// # Ok::<(), dir_spec::DirError>(())
+  /// ```
+  pub fn for_user_in(root: impl Into<PathBuf>, user: impl Into<User>) -> Result<Self, DirError> {
+    let root = root.into();
+    let user = passwd::lookup(Some(&root), &user.into())?;
+    Ok(Self {
+      env: Arc::new(MapEnvironment::new()),
+      passwd_root: Some(root.clone()),
+      platform: Platform::Unix,
+      root: Some(root),
+      system_env: false,
+      user: Some(user),
+      ..Self::new()
+    })
+  }
+
+  /// Creates a resolver for the [current platform](Platform::current) using its [`Convention::Native`]
+  /// fallbacks and the [`SystemEnvironment`] of the current process.
+  pub fn new() -> Self {
+    Self {
+      convention: Convention::default(),
+      env: Arc::new(SystemEnvironment),
+      home_lookup: HomeLookup::default(),
+      passwd_root: None,
+      platform: Platform::current(),
+      root: None,
+      runtime_fallback: RuntimeFallback::default(),
+      snap_home: SnapHome::default(),
+      sudo_home: SudoHome::default(),
+      system_env: true,
+      user: None,
+    }
+  }
+
+  /// Returns the user's binary directory. See [`bin_home()`](crate::bin_home).
+  pub fn bin_home(&self) -> Option<PathBuf> {
+    self.explain_bin_home().into_path()
+  }
+
+  /// Returns the user's cache directory. See [`cache_home()`](crate::cache_home).
+  pub fn cache_home(&self) -> Option<PathBuf> {
+    self.explain_cache_home().into_path()
+  }
+
+  /// Lists every location the configuration named `name` could be in, such as an application's directory.
+  ///
+  /// The location [`config_home()`](Self::config_home) selects comes first, followed by the native and XDG fallbacks
+  /// that `XDG_CONFIG_HOME` or the [`Convention`] may override, such as both `~/Library/Application Support/<name>`
+  /// and `~/.config/<name>` on macOS. Each [`Candidate`] reports whether it exists, so tools can warn when their
+  /// configuration is split across locations.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::Resolver;
+  ///
+  /// let candidates = Resolver::new().config_candidates("bar-app");
+  /// if candidates.iter().filter(|candidate| candidate.exists()).count() > 1 {
+  ///     for candidate in candidates.iter().filter(|candidate| !candidate.selected() && candidate.exists()) {
+  ///         eprintln!("warning: ignoring configuration in {}", candidate.path().display());
+  ///     }
+  /// }
+  /// ```
+  pub fn config_candidates(&self, name: impl AsRef<Path>) -> Vec<Candidate> {
+    candidate::config(self.home_env(), self.explain_config_home(), name.as_ref())
+  }
+
+  /// Returns the system configuration search directories. See [`config_dirs()`](crate::config_dirs).
+  pub fn config_dirs(&self) -> Vec<PathBuf> {
+    dispatch!(self.platform, config_dirs(&self.home_env()))
+  }
+
+  /// Returns the user's configuration directory. See [`config_home()`](crate::config_home).
+  pub fn config_home(&self) -> Option<PathBuf> {
+    self.explain_config_home().into_path()
+  }
+
+  /// Returns the user's local configuration directory. See [`config_local()`](crate::config_local).
+  pub fn config_local(&self) -> Option<PathBuf> {
+    self.explain_config_local().into_path()
+  }
+
+  /// Returns the convention used for platform fallbacks.
+  pub fn convention(&self) -> Convention {
+    self.convention
+  }
+
+  /// Returns the system data search directories. See [`data_dirs()`](crate::data_dirs).
+  pub fn data_dirs(&self) -> Vec<PathBuf> {
+    dispatch!(self.platform, data_dirs(&self.home_env()))
+  }
+
+  /// Returns the user's data directory. See [`data_home()`](crate::data_home).
+  pub fn data_home(&self) -> Option<PathBuf> {
+    self.explain_data_home().into_path()
+  }
+
+  /// Returns the user's local data directory. See [`data_local()`](crate::data_local).
+  pub fn data_local(&self) -> Option<PathBuf> {
+    self.explain_data_local().into_path()
+  }
+
+  /// Returns the user's desktop directory. See [`desktop()`](crate::desktop).
+  pub fn desktop(&self) -> Option<PathBuf> {
+    self.explain_desktop().into_path()
+  }
+
+  /// Returns the user's documents directory. See [`documents()`](crate::documents).
+  pub fn documents(&self) -> Option<PathBuf> {
+    self.explain_documents().into_path()
+  }
+
+  /// Returns the user's downloads directory. See [`downloads()`](crate::downloads).
+  pub fn downloads(&self) -> Option<PathBuf> {
+    self.explain_downloads().into_path()
+  }
+
+  /// Returns the user's cache directory, creating it if it does not exist.
+  /// See [`ensure_cache_home()`](crate::ensure_cache_home).
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Unsupported`] for a resolver with a [`root()`](Self::root), which never creates directories.
+  pub fn ensure_cache_home(&self) -> Result<EnsuredDir, DirError> {
+    self.ensure(self.try_cache_home())
+  }
+
+  /// Returns the user's configuration directory, creating it if it does not exist.
+  /// See [`ensure_config_home()`](crate::ensure_config_home).
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Unsupported`] for a resolver with a [`root()`](Self::root), which never creates directories.
+  pub fn ensure_config_home(&self) -> Result<EnsuredDir, DirError> {
+    self.ensure(self.try_config_home())
+  }
+
+  /// Returns the user's data directory, creating it if it does not exist.
+  /// See [`ensure_data_home()`](crate::ensure_data_home).
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Unsupported`] for a resolver with a [`root()`](Self::root), which never creates directories.
+  pub fn ensure_data_home(&self) -> Result<EnsuredDir, DirError> {
+    self.ensure(self.try_data_home())
+  }
+
+  /// Returns the user's state directory, creating it if it does not exist.
+  /// See [`ensure_state_home()`](crate::ensure_state_home).
+  ///
+  /// # Errors
+  ///
+  /// Returns [`DirError::Unsupported`] for a resolver with a [`root()`](Self::root), which never creates directories.
+  pub fn ensure_state_home(&self) -> Result<EnsuredDir, DirError> {
+    self.ensure(self.try_state_home())
+  }
+
+  /// Returns the environment directories are resolved from.
+  pub fn environment(&self) -> &dyn Environment {
+    self.env.as_ref()
+  }
+
+  /// Explains how [`bin_home()`](Self::bin_home) resolves, including any rejected candidates.
+  pub fn explain_bin_home(&self) -> Explanation {
+    match self.convention {
+      Convention::Native | Convention::Service => dispatch!(self.platform, bin_home(&self.home_env())),
+      Convention::Xdg => xdg::bin_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`cache_home()`](Self::cache_home) resolves, including any rejected candidates.
+  pub fn explain_cache_home(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, cache_home(&self.home_env())),
+      Convention::Service => service::cache_home(self.platform, &self.home_env()),
+      Convention::Xdg => xdg::cache_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`config_home()`](Self::config_home) resolves, including any rejected candidates.
+  pub fn explain_config_home(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, config_home(&self.home_env())),
+      Convention::Service => service::config_home(self.platform, &self.home_env()),
+      Convention::Xdg => xdg::config_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`config_local()`](Self::config_local) resolves, including any rejected candidates.
+  pub fn explain_config_local(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, config_local(&self.home_env())),
+      Convention::Service | Convention::Xdg => self.explain_config_home(),
+    }
+  }
+
+  /// Explains how [`data_home()`](Self::data_home) resolves, including any rejected candidates.
+  pub fn explain_data_home(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, data_home(&self.home_env())),
+      Convention::Service => service::state_home(self.platform, &self.home_env()),
+      Convention::Xdg => xdg::data_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`data_local()`](Self::data_local) resolves, including any rejected candidates.
+  pub fn explain_data_local(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, data_local(&self.home_env())),
+      Convention::Service | Convention::Xdg => self.explain_data_home(),
+    }
+  }
+
+  /// Explains how [`desktop()`](Self::desktop) resolves, including any rejected candidates.
+  pub fn explain_desktop(&self) -> Explanation {
+    dispatch!(self.platform, desktop(&self.home_env()))
+  }
+
+  /// Explains how [`documents()`](Self::documents) resolves, including any rejected candidates.
+  pub fn explain_documents(&self) -> Explanation {
+    dispatch!(self.platform, documents(&self.home_env()))
+  }
+
+  /// Explains how [`downloads()`](Self::downloads) resolves, including any rejected candidates.
+  pub fn explain_downloads(&self) -> Explanation {
+    dispatch!(self.platform, downloads(&self.home_env()))
+  }
+
+  /// Explains how [`fonts()`](Self::fonts) resolves, including any rejected candidates.
+  pub fn explain_fonts(&self) -> Explanation {
+    dispatch!(self.platform, fonts(&self.home_env()))
+  }
+
+  /// Explains how [`home()`](Self::home) resolves, including any rejected candidates.
+  pub fn explain_home(&self) -> Explanation {
+    self.home_env().explain_home()
+  }
+
+  /// Explains how [`logs()`](Self::logs) resolves, including any rejected candidates.
+  pub fn explain_logs(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, logs(&self.home_env())),
+      Convention::Service => service::logs(self.platform, &self.home_env()),
+      Convention::Xdg => xdg::state_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`music()`](Self::music) resolves, including any rejected candidates.
+  pub fn explain_music(&self) -> Explanation {
+    dispatch!(self.platform, music(&self.home_env()))
+  }
+
+  /// Explains how [`pictures()`](Self::pictures) resolves, including any rejected candidates.
+  pub fn explain_pictures(&self) -> Explanation {
+    dispatch!(self.platform, pictures(&self.home_env()))
+  }
+
+  /// Explains how [`preferences()`](Self::preferences) resolves, including any rejected candidates.
+  pub fn explain_preferences(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, preferences(&self.home_env())),
+      Convention::Service | Convention::Xdg => self.explain_config_home(),
+    }
+  }
+
+  /// Explains how [`publicshare()`](Self::publicshare) resolves, including any rejected candidates.
+  pub fn explain_publicshare(&self) -> Explanation {
+    dispatch!(self.platform, publicshare(&self.home_env()))
+  }
+
+  /// Explains how [`runtime()`](Self::runtime) resolves, including any rejected candidates.
+  pub fn explain_runtime(&self) -> Explanation {
+    if self.convention == Convention::Service {
+      return service::runtime(self.platform, &self.home_env());
+    }
+
+    match self.private_runtime_name() {
+      Some(name) => {
+        xdg::resolve_path(self.platform, &self.home_env(), xdg::RUNTIME_DIR).or_else(|| self.private_runtime(name))
+      }
+      None => dispatch!(self.platform, runtime(&self.home_env())),
+    }
+  }
+
+  /// Explains how [`secure_runtime()`](Self::secure_runtime) resolves, including why `XDG_RUNTIME_DIR` was
+  /// rejected.
+  pub fn explain_secure_runtime(&self) -> Explanation {
+    let explanation = secure::runtime(self.platform, &self.home_env(), self.user.as_ref().map(|user| user.uid));
+    match self.private_runtime_name() {
+      Some(name) => explanation.or_else(|| self.private_runtime(name)),
+      None => explanation,
+    }
+  }
+
+  /// Explains how [`state_home()`](Self::state_home) resolves, including any rejected candidates.
+  pub fn explain_state_home(&self) -> Explanation {
+    match self.convention {
+      Convention::Native => dispatch!(self.platform, state_home(&self.home_env())),
+      Convention::Service => service::state_home(self.platform, &self.home_env()),
+      Convention::Xdg => xdg::state_home(self.platform, &self.home_env()),
+    }
+  }
+
+  /// Explains how [`templates()`](Self::templates) resolves, including any rejected candidates.
+  pub fn explain_templates(&self) -> Explanation {
+    dispatch!(self.platform, templates(&self.home_env()))
+  }
+
+  /// Explains how [`videos()`](Self::videos) resolves, including any rejected candidates.
+  pub fn explain_videos(&self) -> Explanation {
+    dispatch!(self.platform, videos(&self.home_env()))
+  }
+
+  /// Returns the first existing match for `relative` in the configuration search path.
+  /// See [`find_config_file()`](crate::find_config_file).
+  pub fn find_config_file(&self, relative: impl AsRef<Path>) -> Option<PathBuf> {
+    self.find_config_files(relative).into_iter().next()
+  }
+
+  /// Returns every existing match for `relative` in the configuration search path.
+  /// See [`find_config_files()`](crate::find_config_files).
+  pub fn find_config_files(&self, relative: impl AsRef<Path>) -> Vec<PathBuf> {
+    xdg::find_files(
+      self.platform,
+      self.root(),
+      self.config_home(),
+      self.config_dirs(),
+      relative.as_ref(),
+    )
+  }
+
+  /// Returns the first existing match for `relative` in the data search path.
+  /// See [`find_data_file()`](crate::find_data_file).
+  pub fn find_data_file(&self, relative: impl AsRef<Path>) -> Option<PathBuf> {
+    self.find_data_files(relative).into_iter().next()
+  }
+
+  /// Returns every existing match for `relative` in the data search path.
+  /// See [`find_data_files()`](crate::find_data_files).
+  pub fn find_data_files(&self, relative: impl AsRef<Path>) -> Vec<PathBuf> {
+    xdg::find_files(
+      self.platform,
+      self.root(),
+      self.data_home(),
+      self.data_dirs(),
+      relative.as_ref(),
+    )
+  }
+
+  /// Detects the Flatpak sandbox the process runs in. See [`flatpak()`](crate::flatpak).
+  pub fn flatpak(&self) -> Option<Flatpak> {
+    Flatpak::detect(self)
+  }
+
+  /// Returns the user's fonts directory. See [`fonts()`](crate::fonts).
+  pub fn fonts(&self) -> Option<PathBuf> {
+    self.explain_fonts().into_path()
+  }
+
+  /// Returns the user's home directory, as reported by the resolver's [`Environment`].
+  pub fn home(&self) -> Option<PathBuf> {
+    self.explain_home().into_path()
+  }
+
+  /// Returns where the current user's home directory is looked up.
+  pub fn home_lookup(&self) -> HomeLookup {
+    self.home_lookup
+  }
+
+  /// Returns where `path`, as seen from inside the [root](Self::root), lives on the host.
+  ///
+  /// Without a root, `path` is returned unchanged.
+  pub fn host_path(&self, path: impl AsRef<Path>) -> PathBuf {
+    environment::beneath(self.root(), path.as_ref())
+  }
+
+  /// Returns the user's log directory. See [`logs()`](crate::logs).
+  pub fn logs(&self) -> Option<PathBuf> {
+    self.explain_logs().into_path()
+  }
+
+  /// Returns the user's music directory. See [`music()`](crate::music).
+  pub fn music(&self) -> Option<PathBuf> {
+    self.explain_music().into_path()
+  }
+
+  /// Returns the user's pictures directory. See [`pictures()`](crate::pictures).
+  pub fn pictures(&self) -> Option<PathBuf> {
+    self.explain_pictures().into_path()
+  }
+
+  /// Returns the directory whose `etc/passwd` file home directories are looked up in, or `None` if they are looked up
+  /// in the system's user database.
+  pub fn passwd_root(&self) -> Option<&Path> {
+    self.passwd_root.as_deref()
+  }
+
+  /// Returns the platform whose rules the resolver follows.
+  pub fn platform(&self) -> Platform {
+    self.platform
+  }
+
+  /// Returns the user's preferences directory. See [`preferences()`](crate::preferences).
+  pub fn preferences(&self) -> Option<PathBuf> {
+    self.explain_preferences().into_path()
+  }
+
+  /// Returns the user's public share directory. See [`publicshare()`](crate::publicshare).
+  pub fn publicshare(&self) -> Option<PathBuf> {
+    self.explain_publicshare().into_path()
+  }
+
+  /// Returns the root of the file system the resolver reads files beneath: the one passed to
+  /// [`for_user_in()`](Self::for_user_in), or otherwise the [root](Environment::root) of its environment.
+  pub fn root(&self) -> Option<&Path> {
+    self.root.as_deref().or_else(|| self.env.root())
+  }
+
+  /// Returns the user's runtime directory. See [`runtime()`](crate::runtime).
+  ///
+  /// With [`RuntimeFallback::Private`] and `XDG_RUNTIME_DIR` unset, this creates the private directory if it does not
+  /// exist yet.
+  pub fn runtime(&self) -> Option<PathBuf> {
+    self.explain_runtime().into_path()
+  }
+
+  /// Returns the fallback used for the runtime directory when `XDG_RUNTIME_DIR` is unset.
+  pub fn runtime_fallback(&self) -> &RuntimeFallback {
+    &self.runtime_fallback
+  }
+
+  /// Returns the user's runtime directory if it is private to the user. See
+  /// [`secure_runtime()`](crate::secure_runtime).
+  ///
+  /// `XDG_RUNTIME_DIR` must be owned by the resolver's user, which is the account passed to
+  /// [`for_user()`](Self::for_user) rather than the current user if set. Like [`runtime()`](Self::runtime), this
+  /// creates the [`RuntimeFallback::Private`] directory if it is needed and does not exist yet.
+  pub fn secure_runtime(&self) -> Option<PathBuf> {
+    self.explain_secure_runtime().into_path()
+  }
+
+  /// Detects the snap the process runs in. See [`snap()`](crate::snap).
+  pub fn snap(&self) -> Option<Snap> {
+    Snap::detect(self)
+  }
+
+  /// Returns which home directory is used inside a snap.
+  pub fn snap_home(&self) -> SnapHome {
+    self.snap_home
+  }
+
+  /// Returns whose home directory is used when the process runs through `sudo` or `pkexec`.
+  pub fn sudo_home(&self) -> SudoHome {
+    self.sudo_home
+  }
+
+  /// Returns the user's state directory. See [`state_home()`](crate::state_home).
+  pub fn state_home(&self) -> Option<PathBuf> {
+    self.explain_state_home().into_path()
+  }
+
+  /// Returns the user's templates directory. See [`templates()`](crate::templates).
+  pub fn templates(&self) -> Option<PathBuf> {
+    self.explain_templates().into_path()
+  }
+
+  /// Returns the [`bin_home()`](Self::bin_home) directory, or why it could not be resolved.
+  pub fn try_bin_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_bin_home().into_result()
+  }
+
+  /// Returns the [`cache_home()`](Self::cache_home) directory, or why it could not be resolved.
+  pub fn try_cache_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_cache_home().into_result()
+  }
+
+  /// Returns the [`config_home()`](Self::config_home) directory, or why it could not be resolved.
+  pub fn try_config_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_config_home().into_result()
+  }
+
+  /// Returns the [`config_local()`](Self::config_local) directory, or why it could not be resolved.
+  pub fn try_config_local(&self) -> Result<PathBuf, DirError> {
+    self.explain_config_local().into_result()
+  }
+
+  /// Returns the [`data_home()`](Self::data_home) directory, or why it could not be resolved.
+  pub fn try_data_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_data_home().into_result()
+  }
+
+  /// Returns the [`data_local()`](Self::data_local) directory, or why it could not be resolved.
+  pub fn try_data_local(&self) -> Result<PathBuf, DirError> {
+    self.explain_data_local().into_result()
+  }
+
+  /// Returns the [`desktop()`](Self::desktop) directory, or why it could not be resolved.
+  pub fn try_desktop(&self) -> Result<PathBuf, DirError> {
+    self.explain_desktop().into_result()
+  }
+
+  /// Returns the [`documents()`](Self::documents) directory, or why it could not be resolved.
+  pub fn try_documents(&self) -> Result<PathBuf, DirError> {
+    self.explain_documents().into_result()
+  }
+
+  /// Returns the [`downloads()`](Self::downloads) directory, or why it could not be resolved.
+  pub fn try_downloads(&self) -> Result<PathBuf, DirError> {
+    self.explain_downloads().into_result()
+  }
+
+  /// Returns the [`fonts()`](Self::fonts) directory, or why it could not be resolved.
+  pub fn try_fonts(&self) -> Result<PathBuf, DirError> {
+    self.explain_fonts().into_result()
+  }
+
+  /// Returns the [`home()`](Self::home) directory, or why it could not be resolved.
+  pub fn try_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_home().into_result()
+  }
+
+  /// Returns the [`logs()`](Self::logs) directory, or why it could not be resolved.
+  pub fn try_logs(&self) -> Result<PathBuf, DirError> {
+    self.explain_logs().into_result()
+  }
+
+  /// Returns the [`music()`](Self::music) directory, or why it could not be resolved.
+  pub fn try_music(&self) -> Result<PathBuf, DirError> {
+    self.explain_music().into_result()
+  }
+
+  /// Returns the [`pictures()`](Self::pictures) directory, or why it could not be resolved.
+  pub fn try_pictures(&self) -> Result<PathBuf, DirError> {
+    self.explain_pictures().into_result()
+  }
+
+  /// Returns the [`preferences()`](Self::preferences) directory, or why it could not be resolved.
+  pub fn try_preferences(&self) -> Result<PathBuf, DirError> {
+    self.explain_preferences().into_result()
+  }
+
+  /// Returns the [`publicshare()`](Self::publicshare) directory, or why it could not be resolved.
+  pub fn try_publicshare(&self) -> Result<PathBuf, DirError> {
+    self.explain_publicshare().into_result()
+  }
+
+  /// Returns the [`runtime()`](Self::runtime) directory, or why it could not be resolved.
+  pub fn try_runtime(&self) -> Result<PathBuf, DirError> {
+    self.explain_runtime().into_result()
+  }
+
+  /// Returns the [`secure_runtime()`](Self::secure_runtime) directory, or why it could not be resolved.
+  pub fn try_secure_runtime(&self) -> Result<PathBuf, DirError> {
+    self.explain_secure_runtime().into_result()
+  }
+
+  /// Returns the [`state_home()`](Self::state_home) directory, or why it could not be resolved.
+  pub fn try_state_home(&self) -> Result<PathBuf, DirError> {
+    self.explain_state_home().into_result()
+  }
+
+  /// Returns the [`templates()`](Self::templates) directory, or why it could not be resolved.
+  pub fn try_templates(&self) -> Result<PathBuf, DirError> {
+    self.explain_templates().into_result()
+  }
+
+  /// Returns the [`videos()`](Self::videos) directory, or why it could not be resolved.
+  pub fn try_videos(&self) -> Result<PathBuf, DirError> {
+    self.explain_videos().into_result()
+  }
+
+  /// Returns the user's videos directory. See [`videos()`](crate::videos).
+  pub fn videos(&self) -> Option<PathBuf> {
+    self.explain_videos().into_path()
+  }
+
+  /// Sets the convention used for platform fallbacks.
+  pub fn with_convention(mut self, convention: Convention) -> Self {
+    self.convention = convention;
+    self
+  }
+
+  /// Resolves directories from `env` instead of the current process environment.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{MapEnvironment, Resolver};
+  ///
+  /// // Resolve on behalf of a captured environment
+  /// let captured: MapEnvironment = [("HOME", "/home/alice")].into_iter().collect();
+  /// let resolver = Resolver::new().with_environment(captured);
+  /// ```
+  pub fn with_environment(mut self, env: impl Environment + 'static) -> Self {
+    self.system_env = (&env as &dyn Any).is::<SystemEnvironment>();
+    self.env = Arc::new(env);
+    self
+  }
+
+  /// Sets where the current user's home directory is looked up.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{HomeLookup, Resolver};
+  ///
+  /// // Still resolves in containers started without HOME
+  /// let resolver = Resolver::new().with_home_lookup(HomeLookup::EnvironmentFirst);
+  /// if let Some(source) = resolver.explain_home().source() {
+  ///     println!("Home directory from {source}");
+  /// }
+  /// ```
+  pub fn with_home_lookup(mut self, home_lookup: HomeLookup) -> Self {
+    self.home_lookup = home_lookup;
+    self
+  }
+
+  /// Reads `etc/passwd` beneath `root` instead of querying the system's user database when looking up home directories
+  /// for [`HomeLookup`] and [`SudoHome`], such as the root of an unpacked container image.
+  pub fn with_passwd_root(mut self, root: impl Into<PathBuf>) -> Self {
+    self.passwd_root = Some(root.into());
+    self
+  }
+
+  /// Resolves directories following the rules of `platform` instead of the current platform.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{MapEnvironment, Platform, Resolver};
+  ///
+  /// // What would the cache directory be on macOS for this user?
+  /// let resolver = Resolver::new()
+  ///   .with_platform(Platform::MacOs)
+  ///   .with_environment(MapEnvironment::new().with_home("/Users/alice"));
+  /// assert_eq!(resolver.cache_home(), Some("/Users/alice/Library/Caches".into()));
+  /// ```
+  pub fn with_platform(mut self, platform: Platform) -> Self {
+    self.platform = platform;
+    self
+  }
+
+  /// Sets which home directory directories are resolved from inside a snap.
+  ///
+  /// With [`SnapHome::Real`], [`home()`](Self::home) and every directory derived from it, such as the `~/.config`
+  /// fallback of [`config_home()`](Self::config_home), use `$SNAP_REAL_HOME` rather than the rewritten `HOME`.
+  /// [`explain_home()`](Self::explain_home) reports which one was used.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{MapEnvironment, Platform, Resolver, SnapHome};
+  /// use std::path::PathBuf;
+  ///
+  /// let env = MapEnvironment::new()
+  ///   .with_home("/home/alice/snap/bar-app/42")
+  ///   .with_var("SNAP_REAL_HOME", "/home/alice");
+  /// let resolver = Resolver::new()
+  ///   .with_platform(Platform::Unix)
+  ///   .with_environment(env)
+  ///   .with_snap_home(SnapHome::Real);
+  /// assert_eq!(resolver.config_home(), Some(PathBuf::from("/home/alice/.config")));
+  /// ```
+  pub fn with_snap_home(mut self, snap_home: SnapHome) -> Self {
+    self.snap_home = snap_home;
+    self
+  }
+
+  /// Sets whose home directory directories are resolved from when the process runs through `sudo` or `pkexec`.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use dir_spec::{Resolver, SudoHome};
+  ///
+  /// // The invoking user's ~/.config, even when sudo reset HOME to root's
+  /// let resolver = Resolver::new().with_sudo_home(SudoHome::Invoking);
+  /// if let Some(source) = resolver.explain_home().source() {
+  ///     println!("Home directory from {source}");
+  /// }
+  /// ```
+  pub fn with_sudo_home(mut self, sudo_home: SudoHome) -> Self {
+    self.sudo_home = sudo_home;
+    self
+  }
+
+  /// Sets the fallback used for the runtime directory when `XDG_RUNTIME_DIR` is unset.
+  ///
+  /// # Examples
+  ///
+  /// ```rust,no_run
+  /// use dir_spec::{Resolver, RuntimeFallback};
+  ///
+  /// // /tmp/bar-app-runtime-<uid> when XDG_RUNTIME_DIR is unset, e.g. under cron
+  /// let resolver = Resolver::new().with_runtime_fallback(RuntimeFallback::Private("bar-app".into()));
+  /// if let Some(runtime) = resolver.runtime() {
+  ///     println!("Socket: {}", runtime.join("bar-app.sock").display());
+  /// }
+  /// ```
+  pub fn with_runtime_fallback(mut self, fallback: RuntimeFallback) -> Self {
+    self.runtime_fallback = fallback;
+    self
+  }
+
+  /// Creates `path` for the resolver's user, unless the resolver reads files beneath a root, whose directories are never
+  /// created on the host.
+  pub(crate) fn ensure(&self, path: Result<PathBuf, DirError>) -> Result<EnsuredDir, DirError> {
+    if self.root().is_some() {
+      return Err(DirError::Unsupported {
+        platform: self.platform,
+      });
+    }
+    path.and_then(|path| ensure::dir(path, self.user.as_ref().map(|user| user.uid)))
+  }
+
+  /// Returns the resolver's environment with the home directory chosen by its home policies.
+  pub(crate) fn home_env(&self) -> HomeEnvironment<'_> {
+    HomeEnvironment {
+      env: self.env.as_ref(),
+      home_lookup: self.home_lookup,
+      passwd_root: self.passwd_root.as_deref(),
+      platform: self.platform,
+      root: self.root(),
+      snap_home: self.snap_home,
+      sudo_home: self.sudo_home,
+      user: self.user.as_ref(),
+    }
+  }
+
+  /// Returns `true` if the resolver reads the current process's environment rather than an injected one.
+  pub(crate) fn uses_system_environment(&self) -> bool {
+    self.system_env
+  }
+
+  /// Resolves the private runtime directory for the resolver's user, which is never looked up beneath a root.
+  fn private_runtime(&self, name: &str) -> Explanation {
+    if self.root().is_some() {
+      return Explanation::unresolved(DirError::Unsupported {
+        platform: self.platform,
+      });
+    }
+    secure::private_runtime(
+      self.platform,
+      &self.home_env(),
+      name,
+      self.user.as_ref().map(|user| user.uid),
+    )
+  }
+
+  fn private_runtime_name(&self) -> Option<&str> {
+    match (&self.runtime_fallback, self.platform) {
+      (RuntimeFallback::Private(name), Platform::MacOs | Platform::Unix) => Some(name),
+      _ => None,
+    }
+  }
+}
+
+impl Default for Resolver {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{MapEnvironment, Source, passwd::tests::image_root};
+
+  fn resolver(env: MapEnvironment) -> Resolver {
+    Resolver::new()
+      .with_platform(Platform::Unix)
+      .with_environment(env.with_home("/home/alice"))
+  }
+
+  mod config_home {
+    use super::*;
+
+    #[test]
+    fn it_ignores_relative_xdg_config_home() {
+      let resolver =
+        resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "relative/config")).with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.config_home(), Some(PathBuf::from("/home/alice/.config")));
+    }
+
+    #[test]
+    fn it_prefers_xdg_config_home_with_xdg_convention() {
+      let resolver =
+        resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "/test/config")).with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.config_home(), Some(PathBuf::from("/test/config")));
+    }
+
+    #[test]
+    fn it_uses_dot_config_with_xdg_convention() {
+      let resolver = resolver(MapEnvironment::new()).with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.config_home(), Some(PathBuf::from("/home/alice/.config")));
+    }
+  }
+
+  mod explain_bin_home {
+    use super::*;
+
+    #[test]
+    fn it_reports_the_variable_and_sub_directory_on_windows() {
+      let env = MapEnvironment::new().with_var("LOCALAPPDATA", "C:\\Users\\alice\\AppData\\Local");
+      let resolver = Resolver::new().with_platform(Platform::Windows).with_environment(env);
+
+      let explanation = resolver.explain_bin_home();
+
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::var(
+          "LOCALAPPDATA",
+          "C:\\Users\\alice\\AppData\\Local",
+          "Programs"
+        ))
+      );
+    }
+  }
+
+  mod explain_config_home {
+    use super::*;
+    use crate::{Rejection, RejectionReason};
+
+    #[test]
+    fn it_reports_a_rejected_relative_path_and_the_fallback() {
+      let resolver =
+        resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "relative/config")).with_convention(Convention::Xdg);
+
+      let explanation = resolver.explain_config_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/home/alice/.config")));
+      assert_eq!(explanation.source(), Some(&Source::home(".config")));
+      assert_eq!(
+        explanation.rejected(),
+        [Rejection::new(
+          "XDG_CONFIG_HOME",
+          "relative/config",
+          RejectionReason::RelativePath
+        )]
+      );
+    }
+
+    #[test]
+    fn it_reports_an_empty_variable() {
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "")).with_convention(Convention::Xdg);
+
+      let explanation = resolver.explain_config_home();
+
+      assert_eq!(explanation.rejected()[0].reason(), RejectionReason::Empty);
+    }
+
+    #[test]
+    fn it_reports_the_xdg_variable() {
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "/srv/config"));
+
+      let explanation = resolver.explain_config_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/srv/config")));
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::var("XDG_CONFIG_HOME", "/srv/config", ""))
+      );
+      assert!(explanation.rejected().is_empty());
+    }
+  }
+
+  mod explain_runtime {
+    use super::*;
+
+    #[test]
+    fn it_reports_the_tmp_default_on_unix() {
+      let resolver = resolver(MapEnvironment::new());
+
+      let explanation = resolver.explain_runtime();
+
+      assert_eq!(explanation.path(), Some(Path::new("/tmp")));
+      assert_eq!(explanation.source(), Some(&Source::Default));
+    }
+  }
+
+  mod find_config_files {
+    use std::fs;
+
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn it_rejects_windows_root_and_drive_relative_paths() {
+      let temp = tempfile::tempdir().unwrap();
+      for name in ["bar-app.toml", "\\bar-app.toml", "C:bar-app.toml"] {
+        fs::write(temp.path().join(name), "").unwrap();
+      }
+      let resolver = Resolver::new()
+        .with_platform(Platform::Windows)
+        .with_environment(MapEnvironment::new().with_var("APPDATA", temp.path()));
+
+      assert_eq!(
+        resolver.find_config_files("bar-app.toml"),
+        [temp.path().join("bar-app.toml")]
+      );
+      assert_eq!(resolver.find_config_files("\\bar-app.toml"), Vec::<PathBuf>::new());
+      assert_eq!(resolver.find_config_files("C:bar-app.toml"), Vec::<PathBuf>::new());
+      assert_eq!(
+        resolver.find_config_files("app\\..\\bar-app.toml"),
+        Vec::<PathBuf>::new()
+      );
+    }
+  }
+
+  mod for_user {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn it_reads_the_home_directory_from_passwd() {
+      let root = Resolver::for_user(0).unwrap();
+
+      let explanation = root.explain_home();
+
+      assert!(explanation.path().is_some());
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::Passwd {
+          file: None,
+          user: "root".to_string(),
+          variable: None,
+        })
+      );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_keeps_the_passwd_home_with_a_captured_environment() {
+      let root = Resolver::for_user(0).unwrap();
+      let home = root.home().unwrap();
+
+      let captured = root.with_environment(MapEnvironment::new().with_home("/somewhere/else"));
+
+      assert_eq!(captured.home(), Some(home));
+    }
+
+    #[test]
+    fn it_reports_an_unknown_user() {
+      let result = Resolver::for_user("dir-spec-no-such-user");
+
+      assert!(matches!(
+        result,
+        Err(DirError::UnknownUser { .. } | DirError::Unsupported { .. })
+      ));
+    }
+  }
+
+  #[cfg(unix)]
+  mod for_user_in {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Writes an image root where `alice` has a French `user-dirs.defaults` and a custom music directory.
+    fn image() -> TempDir {
+      let temp = image_root();
+      let root = temp.path();
+      fs::create_dir_all(root.join("etc/xdg")).unwrap();
+      fs::create_dir_all(root.join("home/alice/.config")).unwrap();
+      fs::write(root.join("etc/xdg/user-dirs.defaults"), "DOWNLOAD=Telechargements\n").unwrap();
+      fs::write(
+        root.join("home/alice/.config/user-dirs.dirs"),
+        "XDG_MUSIC_DIR=\"$HOME/Musique\"\n",
+      )
+      .unwrap();
+      temp
+    }
+
+    #[test]
+    fn it_creates_nothing_outside_the_root() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      fs::create_dir_all(root.join("etc")).unwrap();
+      fs::write(
+        root.join("etc/passwd"),
+        "carol:x:1000:1000::/nonexistent/dir_spec/carol:/bin/sh\n",
+      )
+      .unwrap();
+      let carol = Resolver::for_user_in(root, "carol").unwrap();
+
+      for ensured in [
+        carol.ensure_cache_home(),
+        carol.ensure_config_home(),
+        carol.ensure_data_home(),
+        carol.ensure_state_home(),
+      ] {
+        assert_eq!(
+          ensured,
+          Err(DirError::Unsupported {
+            platform: Platform::Unix
+          })
+        );
+      }
+      assert!(!Path::new("/nonexistent/dir_spec").exists());
+      assert_eq!(fs::read_dir(root).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn it_finds_files_beneath_the_root() {
+      let temp = image();
+      let root = temp.path();
+      fs::create_dir_all(root.join("etc/xdg/dir_spec-image")).unwrap();
+      fs::write(root.join("etc/xdg/dir_spec-image/config.toml"), "").unwrap();
+      let alice = Resolver::for_user_in(root, "alice").unwrap();
+
+      assert_eq!(
+        alice.find_config_file("dir_spec-image/config.toml"),
+        Some(PathBuf::from("/etc/xdg/dir_spec-image/config.toml"))
+      );
+      assert_eq!(
+        alice.find_data_files("dir_spec-image/config.toml"),
+        Vec::<PathBuf>::new()
+      );
+    }
+
+    #[test]
+    fn it_maps_paths_to_the_host() {
+      let temp = image();
+      let root = temp.path();
+      let alice = Resolver::for_user_in(root, "alice").unwrap();
+
+      let config = alice.config_home().unwrap();
+
+      assert_eq!(config, PathBuf::from("/home/alice/.config"));
+      assert_eq!(alice.host_path(&config), root.join("home/alice/.config"));
+    }
+
+    #[test]
+    fn it_reads_user_dirs_beneath_the_root() {
+      let temp = image();
+      let root = temp.path();
+      let alice = Resolver::for_user_in(root, "alice").unwrap();
+
+      let music = alice.explain_music();
+
+      assert_eq!(alice.downloads(), Some(PathBuf::from("/home/alice/Telechargements")));
+      assert_eq!(music.path(), Some(Path::new("/home/alice/Musique")));
+      assert_eq!(
+        music.source(),
+        Some(&Source::UserDirs {
+          file: root.join("home/alice/.config/user-dirs.dirs"),
+          key: "XDG_MUSIC_DIR".to_string(),
+        })
+      );
+    }
+
+    #[test]
+    fn it_reports_a_user_missing_from_the_root() {
+      let temp = image();
+      let root = temp.path();
+
+      assert!(matches!(
+        Resolver::for_user_in(root, "bob"),
+        Err(DirError::UnknownUser { .. })
+      ));
+    }
+  }
+
+  mod logs {
+    use super::*;
+
+    #[test]
+    fn it_prefers_xdg_state_home_on_macos() {
+      let resolver =
+        resolver(MapEnvironment::new().with_var("XDG_STATE_HOME", "/test/state")).with_platform(Platform::MacOs);
+
+      assert_eq!(resolver.logs(), Some(PathBuf::from("/test/state")));
+    }
+
+    #[test]
+    fn it_uses_library_logs_on_macos() {
+      let resolver = resolver(MapEnvironment::new()).with_platform(Platform::MacOs);
+
+      assert_eq!(resolver.logs(), Some(PathBuf::from("/home/alice/Library/Logs")));
+    }
+
+    #[test]
+    fn it_uses_local_app_data_on_windows() {
+      let env = MapEnvironment::new().with_var("LOCALAPPDATA", "C:\\Users\\alice\\AppData\\Local");
+      let resolver = Resolver::new().with_environment(env).with_platform(Platform::Windows);
+
+      assert_eq!(resolver.logs(), Some(PathBuf::from("C:\\Users\\alice\\AppData\\Local")));
+    }
+
+    #[test]
+    fn it_uses_state_home_on_unix() {
+      let resolver = resolver(MapEnvironment::new());
+
+      assert_eq!(resolver.logs(), Some(PathBuf::from("/home/alice/.local/state")));
+    }
+  }
+
+  mod new {
+    use temp_env::with_var;
+
+    use super::*;
+
+    #[test]
+    fn it_matches_module_functions() {
+      let resolver = Resolver::new();
+
+      with_var("XDG_CONFIG_HOME", None::<&str>, || {
+        assert_eq!(resolver.config_home(), crate::config_home());
+        assert_eq!(resolver.home(), crate::home());
+      });
+    }
+  }
+
+  mod preferences {
+    use super::*;
+
+    #[test]
+    fn it_matches_config_home_with_xdg_convention() {
+      let resolver = resolver(MapEnvironment::new()).with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.preferences(), resolver.config_home());
+    }
+  }
+
+  mod root {
+    use std::{ffi::OsString, fs};
+
+    use super::*;
+
+    /// An environment for `alice` inside an image root, as a caller might implement it.
+    #[derive(Debug)]
+    struct ImageEnvironment {
+      root: PathBuf,
+    }
+
+    impl Environment for ImageEnvironment {
+      fn home_dir(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/home/alice"))
+      }
+
+      fn root(&self) -> Option<&Path> {
+        Some(&self.root)
+      }
+
+      fn var_os(&self, _key: &str) -> Option<OsString> {
+        None
+      }
+    }
+
+    #[test]
+    fn it_honors_the_environment_root() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      fs::create_dir_all(root.join("home/alice/.config/bar-app")).unwrap();
+      fs::write(
+        root.join("home/alice/.config/user-dirs.dirs"),
+        "XDG_MUSIC_DIR=\"$HOME/Musique\"\n",
+      )
+      .unwrap();
+      fs::write(root.join("home/alice/.config/bar-app/config.toml"), "").unwrap();
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(ImageEnvironment {
+          root: root.to_path_buf(),
+        });
+
+      assert_eq!(resolver.root(), Some(root));
+      assert_eq!(resolver.music(), Some(PathBuf::from("/home/alice/Musique")));
+      assert_eq!(
+        resolver.find_config_file("bar-app/config.toml"),
+        Some(PathBuf::from("/home/alice/.config/bar-app/config.toml"))
+      );
+      assert_eq!(
+        resolver.host_path("/home/alice/.config"),
+        root.join("home/alice/.config")
+      );
+      assert!(matches!(
+        resolver.ensure_config_home(),
+        Err(DirError::Unsupported { .. })
+      ));
+    }
+
+    #[test]
+    fn it_prefers_the_root_of_for_user_in() {
+      let temp = image_root();
+      let root = temp.path();
+
+      let alice = Resolver::for_user_in(root, "alice")
+        .unwrap()
+        .with_environment(ImageEnvironment {
+          root: PathBuf::from("/elsewhere"),
+        });
+
+      assert_eq!(alice.root(), Some(root));
+    }
+  }
+
+  mod try_cache_home {
+    use super::*;
+    use crate::RejectionReason;
+
+    #[test]
+    fn it_reports_a_missing_home_directory() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new());
+
+      assert_eq!(resolver.try_cache_home(), Err(DirError::MissingHome));
+    }
+
+    #[test]
+    fn it_reports_an_invalid_value_when_no_fallback_applies() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(MapEnvironment::new().with_var("XDG_CACHE_HOME", "cache"));
+
+      assert_eq!(
+        resolver.try_cache_home(),
+        Err(DirError::InvalidValue {
+          reason: RejectionReason::RelativePath,
+          value: "cache".into(),
+          variable: "XDG_CACHE_HOME".to_string(),
+        })
+      );
+    }
+
+    #[test]
+    fn it_returns_the_resolved_path() {
+      let resolver = resolver(MapEnvironment::new());
+
+      assert_eq!(resolver.try_cache_home(), Ok(PathBuf::from("/home/alice/.cache")));
+    }
+  }
+
+  mod try_data_local {
+    use super::*;
+
+    #[test]
+    fn it_reports_a_missing_platform_variable_on_windows() {
+      let resolver = resolver(MapEnvironment::new()).with_platform(Platform::Windows);
+
+      assert_eq!(
+        resolver.try_data_local(),
+        Err(DirError::MissingVariable {
+          variable: "LOCALAPPDATA".to_string(),
+        })
+      );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_reports_values_that_are_not_unicode_for_another_platform() {
+      use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+      let value = OsString::from_vec(b"C:\\\xff".to_vec());
+      let resolver =
+        resolver(MapEnvironment::new().with_var("LOCALAPPDATA", value.clone())).with_platform(Platform::Windows);
+
+      assert_eq!(
+        resolver.try_data_local(),
+        Err(DirError::NotUnicode {
+          value,
+          variable: "LOCALAPPDATA".to_string(),
+        })
+      );
+    }
+  }
+
+  mod try_fonts {
+    use super::*;
+
+    #[test]
+    fn it_reports_unsupported_on_windows() {
+      let resolver = resolver(MapEnvironment::new()).with_platform(Platform::Windows);
+
+      assert_eq!(
+        resolver.try_fonts(),
+        Err(DirError::Unsupported {
+          platform: Platform::Windows,
+        })
+      );
+    }
+  }
+
+  mod with_convention {
+    use super::*;
+
+    #[test]
+    fn it_applies_xdg_defaults_to_all_base_directories() {
+      let resolver = resolver(MapEnvironment::new()).with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.bin_home(), Some(PathBuf::from("/home/alice/.local/bin")));
+      assert_eq!(resolver.cache_home(), Some(PathBuf::from("/home/alice/.cache")));
+      assert_eq!(resolver.config_local(), Some(PathBuf::from("/home/alice/.config")));
+      assert_eq!(resolver.data_home(), Some(PathBuf::from("/home/alice/.local/share")));
+      assert_eq!(resolver.data_local(), Some(PathBuf::from("/home/alice/.local/share")));
+      assert_eq!(resolver.state_home(), Some(PathBuf::from("/home/alice/.local/state")));
+    }
+
+    #[test]
+    fn it_applies_system_defaults_with_service_convention() {
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_CONFIG_HOME", "/test/config"))
+        .with_convention(Convention::Service);
+
+      assert_eq!(resolver.cache_home(), Some(PathBuf::from("/var/cache")));
+      assert_eq!(resolver.config_home(), Some(PathBuf::from("/etc")));
+      assert_eq!(resolver.data_home(), Some(PathBuf::from("/var/lib")));
+      assert_eq!(resolver.runtime(), Some(PathBuf::from("/run")));
+      assert_eq!(resolver.state_home(), Some(PathBuf::from("/var/lib")));
+    }
+
+    #[test]
+    fn it_prefers_systemd_directories_with_service_convention() {
+      let env = MapEnvironment::new()
+        .with_var("CACHE_DIRECTORY", "/var/cache/bar-app")
+        .with_var("RUNTIME_DIRECTORY", "/run/bar-app")
+        .with_var("STATE_DIRECTORY", "/var/lib/bar-app:/var/lib/bar-app-extra");
+      let resolver = resolver(env).with_convention(Convention::Service);
+
+      assert_eq!(resolver.cache_home(), Some(PathBuf::from("/var/cache/bar-app")));
+      assert_eq!(resolver.runtime(), Some(PathBuf::from("/run/bar-app")));
+      assert_eq!(resolver.state_home(), Some(PathBuf::from("/var/lib/bar-app")));
+    }
+  }
+
+  mod with_environment {
+    use super::*;
+
+    #[test]
+    fn it_reads_variables_from_environment() {
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_CACHE_HOME", "/var/cache/alice"));
+
+      assert_eq!(resolver.cache_home(), Some(PathBuf::from("/var/cache/alice")));
+    }
+
+    #[test]
+    fn it_returns_none_without_home() {
+      let resolver = Resolver::new()
+        .with_environment(MapEnvironment::new())
+        .with_convention(Convention::Xdg);
+
+      assert_eq!(resolver.config_home(), None);
+    }
+
+    #[test]
+    fn it_splits_search_paths_from_environment() {
+      let dirs = std::env::join_paths(["/opt/share", "relative", "/usr/share"]).unwrap();
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_DATA_DIRS", dirs));
+
+      assert_eq!(
+        resolver.data_dirs(),
+        vec![PathBuf::from("/opt/share"), PathBuf::from("/usr/share")]
+      );
+    }
+  }
+
+  mod with_platform {
+    use super::*;
+
+    #[test]
+    fn it_resolves_macos_rules() {
+      let resolver = resolver(MapEnvironment::new()).with_platform(Platform::MacOs);
+
+      assert_eq!(
+        resolver.config_home(),
+        Some(PathBuf::from("/home/alice/Library/Application Support"))
+      );
+      assert_eq!(resolver.videos(), Some(PathBuf::from("/home/alice/Movies")));
+    }
+
+    #[test]
+    fn it_resolves_unix_rules() {
+      let resolver = resolver(MapEnvironment::new());
+
+      assert_eq!(resolver.state_home(), Some(PathBuf::from("/home/alice/.local/state")));
+    }
+
+    #[test]
+    fn it_resolves_windows_rules() {
+      let env = MapEnvironment::new()
+        .with_var("APPDATA", "C:\\Users\\alice\\AppData\\Roaming")
+        .with_var("LOCALAPPDATA", "C:\\Users\\alice\\AppData\\Local")
+        .with_var("USERPROFILE", "C:\\Users\\alice");
+      let resolver = Resolver::new().with_environment(env).with_platform(Platform::Windows);
+
+      assert_eq!(
+        resolver.config_home(),
+        Some(PathBuf::from("C:\\Users\\alice\\AppData\\Roaming"))
+      );
+      assert_eq!(
+        resolver.bin_home(),
+        Some(PathBuf::from("C:\\Users\\alice\\AppData\\Local\\Programs"))
+      );
+      assert_eq!(resolver.music(), Some(PathBuf::from("C:\\Users\\alice\\Music")));
+      assert_eq!(resolver.fonts(), None);
+    }
+
+    #[test]
+    fn it_validates_xdg_variables_with_platform_rules() {
+      let env = MapEnvironment::new()
+        .with_var("XDG_CONFIG_HOME", "D:\\config")
+        .with_var("APPDATA", "C:\\Users\\alice\\AppData\\Roaming");
+      let windows = Resolver::new()
+        .with_environment(env.clone())
+        .with_platform(Platform::Windows);
+      let unix = resolver(env).with_platform(Platform::Unix);
+
+      assert_eq!(windows.config_home(), Some(PathBuf::from("D:\\config")));
+      assert_eq!(unix.config_home(), Some(PathBuf::from("/home/alice/.config")));
+    }
+  }
+
+  mod with_snap_home {
+    use super::*;
+
+    fn snap_env() -> MapEnvironment {
+      MapEnvironment::new()
+        .with_home("/home/alice/snap/bar-app/42")
+        .with_var("SNAP_REAL_HOME", "/home/alice")
+    }
+
+    #[test]
+    fn it_keeps_the_rewritten_home_by_default() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(snap_env());
+
+      assert_eq!(resolver.home(), Some(PathBuf::from("/home/alice/snap/bar-app/42")));
+    }
+
+    #[test]
+    fn it_reports_the_real_home_variable() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(snap_env())
+        .with_snap_home(SnapHome::Real);
+
+      let explanation = resolver.explain_home();
+
+      assert_eq!(explanation.path(), Some(Path::new("/home/alice")));
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::var("SNAP_REAL_HOME", "/home/alice", ""))
+      );
+    }
+
+    #[test]
+    fn it_resolves_directories_from_the_real_home() {
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(snap_env())
+        .with_snap_home(SnapHome::Real);
+
+      assert_eq!(resolver.config_home(), Some(PathBuf::from("/home/alice/.config")));
+      assert_eq!(resolver.music(), Some(PathBuf::from("/home/alice/Music")));
+    }
+
+    #[test]
+    fn it_uses_home_outside_a_snap() {
+      let resolver = resolver(MapEnvironment::new()).with_snap_home(SnapHome::Real);
+
+      assert_eq!(resolver.home(), Some(PathBuf::from("/home/alice")));
+    }
+  }
+
+  mod with_runtime_fallback {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn it_creates_a_private_directory_when_xdg_runtime_dir_is_unset() {
+      let temp = tempfile::tempdir().unwrap();
+      let base = temp.path();
+      let resolver = resolver(MapEnvironment::new().with_var("TMPDIR", base))
+        .with_runtime_fallback(RuntimeFallback::Private("bar-app".to_string()));
+
+      let runtime = resolver.runtime().unwrap();
+
+      assert_eq!(runtime.parent(), Some(base));
+      assert!(runtime.is_dir());
+      assert_eq!(resolver.secure_runtime(), Some(runtime));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_creates_nothing_for_another_user() {
+      let temp = tempfile::tempdir().unwrap();
+      let base = temp.path();
+      let root = Resolver::for_user(0)
+        .unwrap()
+        .with_environment(MapEnvironment::new().with_var("TMPDIR", base))
+        .with_runtime_fallback(RuntimeFallback::Private("bar-app".to_string()));
+
+      assert_eq!(root.runtime(), None);
+      assert_eq!(root.secure_runtime(), None);
+      assert_eq!(std::fs::read_dir(base).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn it_does_not_resolve_a_private_directory_beneath_a_root() {
+      let temp = image_root();
+      let root = temp.path();
+      let alice = Resolver::for_user_in(root, "alice")
+        .unwrap()
+        .with_runtime_fallback(RuntimeFallback::Private("bar-app".to_string()));
+
+      assert_eq!(
+        alice.try_runtime(),
+        Err(DirError::Unsupported {
+          platform: Platform::Unix
+        })
+      );
+    }
+
+    #[test]
+    fn it_keeps_temp_on_windows() {
+      let resolver = resolver(MapEnvironment::new().with_var("TEMP", "C:\\Temp"))
+        .with_platform(Platform::Windows)
+        .with_runtime_fallback(RuntimeFallback::Private("bar-app".to_string()));
+
+      assert_eq!(resolver.runtime(), Some(PathBuf::from("C:\\Temp")));
+    }
+
+    #[test]
+    fn it_prefers_xdg_runtime_dir() {
+      let resolver = resolver(MapEnvironment::new().with_var("XDG_RUNTIME_DIR", "/run/user/1000"))
+        .with_runtime_fallback(RuntimeFallback::Private("bar-app".to_string()));
+
+      assert_eq!(resolver.runtime(), Some(PathBuf::from("/run/user/1000")));
+    }
+  }
+}
@@ -0,0 +1,402 @@
+#[cfg(unix)]
+use std::{
+  fs::{self, DirBuilder},
+  io::ErrorKind,
+  os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+  path::{Path, PathBuf},
+};
+
+use crate::{DirError, Environment, Explanation, Platform, xdg};
+#[cfg(unix)]
+use crate::{Rejection, RejectionReason, Source};
+
+#[cfg(unix)]
+const PRIVATE_MODE: u32 = 0o700;
+
+/// Checks that `path` is a real directory owned by `uid` that only its owner can access.
+#[cfg(unix)]
+pub fn check_private_dir(path: &Path, uid: u32) -> Result<(), RejectionReason> {
+  let metadata = fs::symlink_metadata(path).map_err(|_| RejectionReason::NotFound)?;
+  if metadata.file_type().is_symlink() {
+    return Err(RejectionReason::Symlink);
+  }
+  if !metadata.is_dir() {
+    return Err(RejectionReason::NotDirectory);
+  }
+  if metadata.uid() != uid {
+    return Err(RejectionReason::UnsafeOwner {
+      uid: metadata.uid(),
+    });
+  }
+
+  let mode = metadata.permissions().mode() & 0o7777;
+  if mode & 0o777 != PRIVATE_MODE {
+    return Err(RejectionReason::UnsafeMode {
+      mode,
+    });
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+pub fn effective_uid() -> u32 {
+  // SAFETY: `geteuid` has no preconditions and always succeeds
+  unsafe { libc::geteuid() }
+}
+
+/// Creates `path` with mode `0700`, or reuses it if it is already a private directory owned by `uid`.
+///
+/// Returns whether the directory was created. An existing symbolic link, file, or directory that is owned by another
+/// user or accessible to others is never used or modified.
+#[cfg(unix)]
+pub fn private_dir(path: &Path, uid: u32) -> Result<bool, RejectionReason> {
+  match DirBuilder::new().mode(PRIVATE_MODE).create(path) {
+    Ok(()) => {
+      // The umask may have removed owner bits; it can never have added group or world bits
+      fs::set_permissions(path, fs::Permissions::from_mode(PRIVATE_MODE)).map_err(|_| RejectionReason::NotFound)?;
+      check_private_dir(path, uid).map(|()| true)
+    }
+    Err(error) if error.kind() == ErrorKind::AlreadyExists => check_private_dir(path, uid).map(|()| false),
+    Err(_) => Err(RejectionReason::NotFound),
+  }
+}
+
+/// Resolves a private per-user directory named `<name>-runtime-<uid>` beneath `$TMPDIR` or `/tmp`.
+///
+/// The directory is created if needed for the effective user, when `user` is `None`. Another user's directory is
+/// only reused if it already exists, so nothing is created on their behalf.
+pub fn private_runtime(platform: Platform, env: &dyn Environment, name: &str, user: Option<u32>) -> Explanation {
+  #[cfg(unix)]
+  {
+    let base = xdg::resolve_path(platform, env, "TMPDIR")
+      .into_path()
+      .unwrap_or_else(|| PathBuf::from("/tmp"));
+    let uid = user.unwrap_or_else(effective_uid);
+    let path = platform.join(base, format!("{name}-runtime-{uid}"));
+
+    let private = match user {
+      Some(uid) => check_private_dir(&path, uid).map(|()| false),
+      None => private_dir(&path, uid),
+    };
+    match private {
+      Ok(created) => Explanation::resolved(
+        path,
+        Source::PrivateRuntime {
+          created,
+        },
+      ),
+      Err(reason) => Explanation::unresolved(DirError::Insecure {
+        path,
+        reason,
+      }),
+    }
+  }
+
+  #[cfg(not(unix))]
+  {
+    let _ = (env, name, user);
+    Explanation::unresolved(DirError::Unsupported {
+      platform,
+    })
+  }
+}
+
+/// Resolves `XDG_RUNTIME_DIR`, rejecting it unless it is a private directory owned by `user`, or by the effective user
+/// when `user` is `None`.
+pub fn runtime(platform: Platform, env: &dyn Environment, user: Option<u32>) -> Explanation {
+  let explanation = xdg::resolve_path(platform, env, xdg::RUNTIME_DIR);
+
+  #[cfg(unix)]
+  {
+    let Some(path) = explanation.path() else {
+      return explanation;
+    };
+
+    match check_private_dir(path, user.unwrap_or_else(effective_uid)) {
+      Ok(()) => explanation,
+      Err(reason) => Explanation::unresolved(DirError::MissingVariable {
+        variable: xdg::RUNTIME_DIR.to_string(),
+      })
+      .reject(Rejection::new(xdg::RUNTIME_DIR, path, reason)),
+    }
+  }
+
+  #[cfg(not(unix))]
+  {
+    let _ = (explanation, user);
+    Explanation::unresolved(DirError::Unsupported {
+      platform,
+    })
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use std::os::unix::fs::symlink;
+
+  use tempfile::TempDir;
+
+  use super::*;
+
+  /// Creates a temporary directory with the mode `XDG_RUNTIME_DIR` requires.
+  fn private_temp_dir() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::set_permissions(dir.path(), fs::Permissions::from_mode(PRIVATE_MODE)).unwrap();
+    dir
+  }
+
+  mod check_private_dir {
+    use super::*;
+
+    #[test]
+    fn it_accepts_a_private_directory_owned_by_the_user() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+
+      assert_eq!(check_private_dir(dir, effective_uid()), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_a_directory_owned_by_someone_else() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let uid = effective_uid();
+
+      assert_eq!(
+        check_private_dir(dir, uid.wrapping_add(1)),
+        Err(RejectionReason::UnsafeOwner {
+          uid
+        })
+      );
+    }
+
+    #[test]
+    fn it_rejects_a_file() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let file = dir.join("file");
+      fs::write(&file, "").unwrap();
+
+      assert_eq!(
+        check_private_dir(&file, effective_uid()),
+        Err(RejectionReason::NotDirectory)
+      );
+    }
+
+    #[test]
+    fn it_rejects_a_missing_directory() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+
+      assert_eq!(
+        check_private_dir(&dir.join("missing"), effective_uid()),
+        Err(RejectionReason::NotFound)
+      );
+    }
+
+    #[test]
+    fn it_rejects_a_symlink_to_a_private_directory() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let link = dir.join("link");
+      symlink(dir, &link).unwrap();
+
+      assert_eq!(check_private_dir(&link, effective_uid()), Err(RejectionReason::Symlink));
+    }
+
+    #[test]
+    fn it_rejects_group_or_world_access() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      fs::set_permissions(dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+      assert_eq!(
+        check_private_dir(dir, effective_uid()),
+        Err(RejectionReason::UnsafeMode {
+          mode: 0o755
+        })
+      );
+    }
+  }
+
+  mod private_dir {
+    use super::*;
+
+    #[test]
+    fn it_creates_a_private_directory() {
+      let temp = private_temp_dir();
+      let path = temp.path().join("runtime");
+
+      let created = private_dir(&path, effective_uid());
+
+      assert_eq!(created, Ok(true));
+      assert_eq!(
+        fs::symlink_metadata(&path).unwrap().permissions().mode() & 0o777,
+        PRIVATE_MODE
+      );
+    }
+
+    #[test]
+    fn it_refuses_a_directory_with_open_permissions() {
+      let temp = private_temp_dir();
+      let path = temp.path().join("runtime");
+      fs::create_dir(&path).unwrap();
+      fs::set_permissions(&path, fs::Permissions::from_mode(0o777)).unwrap();
+
+      assert_eq!(
+        private_dir(&path, effective_uid()),
+        Err(RejectionReason::UnsafeMode {
+          mode: 0o777
+        })
+      );
+      assert_eq!(fs::symlink_metadata(&path).unwrap().permissions().mode() & 0o777, 0o777);
+    }
+
+    #[test]
+    fn it_refuses_a_planted_symlink() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let target = dir.join("target");
+      fs::create_dir(&target).unwrap();
+      fs::set_permissions(&target, fs::Permissions::from_mode(PRIVATE_MODE)).unwrap();
+      let path = dir.join("runtime");
+      symlink(&target, &path).unwrap();
+
+      assert_eq!(private_dir(&path, effective_uid()), Err(RejectionReason::Symlink));
+    }
+
+    #[test]
+    fn it_reuses_an_existing_private_directory() {
+      let temp = private_temp_dir();
+      let path = temp.path().join("runtime");
+      private_dir(&path, effective_uid()).unwrap();
+
+      assert_eq!(private_dir(&path, effective_uid()), Ok(false));
+    }
+  }
+
+  mod private_runtime {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_names_the_directory_after_the_app_and_uid() {
+      let temp = private_temp_dir();
+      let base = temp.path();
+      let env = MapEnvironment::new().with_var("TMPDIR", base);
+
+      let explanation = private_runtime(Platform::Unix, &env, "bar-app", None);
+
+      assert_eq!(
+        explanation.path(),
+        Some(base.join(format!("bar-app-runtime-{}", effective_uid())).as_path())
+      );
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::PrivateRuntime {
+          created: true
+        })
+      );
+    }
+
+    #[test]
+    fn it_never_creates_another_users_directory() {
+      let temp = private_temp_dir();
+      let base = temp.path();
+      let env = MapEnvironment::new().with_var("TMPDIR", base);
+      let uid = effective_uid().wrapping_add(1);
+
+      let explanation = private_runtime(Platform::Unix, &env, "bar-app", Some(uid));
+
+      assert_eq!(
+        explanation.into_result(),
+        Err(DirError::Insecure {
+          path: base.join(format!("bar-app-runtime-{uid}")),
+          reason: RejectionReason::NotFound,
+        })
+      );
+      assert_eq!(fs::read_dir(base).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn it_reuses_another_users_existing_directory() {
+      let temp = private_temp_dir();
+      let base = temp.path();
+      let env = MapEnvironment::new().with_var("TMPDIR", base);
+      let uid = effective_uid();
+      private_dir(&base.join(format!("bar-app-runtime-{uid}")), uid).unwrap();
+
+      let explanation = private_runtime(Platform::Unix, &env, "bar-app", Some(uid));
+
+      assert_eq!(
+        explanation.path(),
+        Some(base.join(format!("bar-app-runtime-{uid}")).as_path())
+      );
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::PrivateRuntime {
+          created: false
+        })
+      );
+    }
+  }
+
+  mod runtime {
+    use super::*;
+    use crate::MapEnvironment;
+
+    #[test]
+    fn it_rejects_an_insecure_xdg_runtime_dir() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      fs::set_permissions(dir, fs::Permissions::from_mode(0o777)).unwrap();
+      let env = MapEnvironment::new().with_var("XDG_RUNTIME_DIR", dir);
+
+      let explanation = runtime(Platform::Unix, &env, None);
+
+      assert_eq!(explanation.path(), None);
+      assert_eq!(
+        explanation.rejected(),
+        [Rejection::new(
+          "XDG_RUNTIME_DIR",
+          dir,
+          RejectionReason::UnsafeMode {
+            mode: 0o777
+          }
+        )]
+      );
+    }
+
+    #[test]
+    fn it_checks_ownership_against_the_given_user() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let env = MapEnvironment::new().with_var("XDG_RUNTIME_DIR", dir);
+      let uid = effective_uid();
+
+      let explanation = runtime(Platform::Unix, &env, Some(uid.wrapping_add(1)));
+
+      assert_eq!(explanation.path(), None);
+      assert_eq!(
+        explanation.rejected(),
+        [Rejection::new(
+          "XDG_RUNTIME_DIR",
+          dir,
+          RejectionReason::UnsafeOwner {
+            uid
+          }
+        )]
+      );
+    }
+
+    #[test]
+    fn it_returns_a_private_xdg_runtime_dir() {
+      let temp = private_temp_dir();
+      let dir = temp.path();
+      let env = MapEnvironment::new().with_var("XDG_RUNTIME_DIR", dir);
+
+      assert_eq!(runtime(Platform::Unix, &env, None).path(), Some(dir));
+    }
+  }
+}
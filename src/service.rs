@@ -0,0 +1,178 @@
+use std::path::PathBuf;
+
+use crate::{
+  DirError, Environment, Platform,
+  explain::{Explanation, Rejection, RejectionReason, Source},
+  system::{self, Kind},
+};
+
+pub const CACHE_DIRECTORY: &str = "CACHE_DIRECTORY";
+pub const CONFIGURATION_DIRECTORY: &str = "CONFIGURATION_DIRECTORY";
+pub const LOGS_DIRECTORY: &str = "LOGS_DIRECTORY";
+pub const RUNTIME_DIRECTORY: &str = "RUNTIME_DIRECTORY";
+pub const STATE_DIRECTORY: &str = "STATE_DIRECTORY";
+
+pub fn cache_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve(platform, env, CACHE_DIRECTORY, Kind::Cache)
+}
+
+pub fn config_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve(platform, env, CONFIGURATION_DIRECTORY, Kind::Config)
+}
+
+/// Returns the first directory systemd exported in `key`, if any.
+pub fn directory(platform: Platform, env: &dyn Environment, key: &str) -> Option<PathBuf> {
+  resolve_var(platform, env, key).into_path()
+}
+
+pub fn logs(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve(platform, env, LOGS_DIRECTORY, Kind::Logs)
+}
+
+pub fn runtime(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve(platform, env, RUNTIME_DIRECTORY, Kind::Runtime)
+}
+
+pub fn state_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve(platform, env, STATE_DIRECTORY, Kind::State)
+}
+
+/// Resolves the directory systemd exported in `key`, falling back to the system-wide directory of [`SystemDirs`].
+///
+/// [`SystemDirs`]: crate::SystemDirs
+fn resolve(platform: Platform, env: &dyn Environment, key: &str, kind: Kind) -> Explanation {
+  resolve_var(platform, env, key).or_else(|| system::resolve_default(platform, env, kind))
+}
+
+/// Resolves the first entry of the colon-separated list systemd exports for `StateDirectory=` and friends.
+fn resolve_var(platform: Platform, env: &dyn Environment, key: &str) -> Explanation {
+  let missing = || {
+    Explanation::unresolved(DirError::MissingVariable {
+      variable: key.to_string(),
+    })
+  };
+  let Some(value) = env.var_os(key) else {
+    return missing();
+  };
+
+  let first = platform.split_paths(&value).into_iter().next().unwrap_or_default();
+  if first.as_os_str().is_empty() {
+    missing().reject(Rejection::new(key, value, RejectionReason::Empty))
+  } else if !platform.is_representable(&value) {
+    missing().reject(Rejection::new(key, value, RejectionReason::NotUnicode))
+  } else if platform.is_absolute(&first) {
+    Explanation::resolved(first, Source::var(key, value, ""))
+  } else {
+    missing().reject(Rejection::new(key, value, RejectionReason::RelativePath))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  use super::*;
+  use crate::MapEnvironment;
+
+  mod resolve {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_library_on_macos() {
+      let env = MapEnvironment::new();
+
+      assert_eq!(
+        cache_home(Platform::MacOs, &env).path(),
+        Some(Path::new("/Library/Caches"))
+      );
+      assert_eq!(
+        config_home(Platform::MacOs, &env).path(),
+        Some(Path::new("/Library/Application Support"))
+      );
+      assert_eq!(logs(Platform::MacOs, &env).path(), Some(Path::new("/Library/Logs")));
+      assert_eq!(runtime(Platform::MacOs, &env).path(), Some(Path::new("/var/run")));
+      assert_eq!(
+        state_home(Platform::MacOs, &env).path(),
+        Some(Path::new("/Library/Application Support"))
+      );
+    }
+
+    #[test]
+    fn it_matches_system_dirs() {
+      for platform in [Platform::MacOs, Platform::Unix, Platform::Windows] {
+        let env = MapEnvironment::new().with_var("PROGRAMDATA", "C:\\ProgramData");
+        let system = crate::SystemDirs::new().with_resolver(
+          crate::Resolver::new()
+            .with_platform(platform)
+            .with_environment(env.clone()),
+        );
+
+        assert_eq!(cache_home(platform, &env).into_path(), system.cache());
+        assert_eq!(config_home(platform, &env).into_path(), system.config());
+        assert_eq!(logs(platform, &env).into_path(), system.logs());
+        assert_eq!(runtime(platform, &env).into_path(), system.runtime());
+        assert_eq!(state_home(platform, &env).into_path(), system.state());
+      }
+    }
+  }
+
+  mod resolve_var {
+    use super::*;
+
+    #[test]
+    fn it_rejects_a_relative_directory() {
+      let env = MapEnvironment::new().with_var(STATE_DIRECTORY, "bar-app");
+
+      let explanation = resolve_var(Platform::Unix, &env, STATE_DIRECTORY);
+
+      assert_eq!(explanation.path(), None);
+      assert_eq!(
+        explanation.rejected(),
+        [Rejection::new(
+          STATE_DIRECTORY,
+          "bar-app",
+          RejectionReason::RelativePath
+        )]
+      );
+    }
+
+    #[test]
+    fn it_returns_the_first_directory() {
+      let env = MapEnvironment::new().with_var(STATE_DIRECTORY, "/var/lib/bar-app:/var/lib/bar-app-extra");
+
+      let explanation = resolve_var(Platform::Unix, &env, STATE_DIRECTORY);
+
+      assert_eq!(explanation.path(), Some(Path::new("/var/lib/bar-app")));
+      assert_eq!(
+        explanation.source(),
+        Some(&Source::var(
+          STATE_DIRECTORY,
+          "/var/lib/bar-app:/var/lib/bar-app-extra",
+          ""
+        ))
+      );
+    }
+  }
+
+  mod state_home {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_var_lib() {
+      let explanation = state_home(Platform::Unix, &MapEnvironment::new());
+
+      assert_eq!(explanation.path(), Some(Path::new("/var/lib")));
+      assert_eq!(explanation.source(), Some(&Source::Default));
+    }
+
+    #[test]
+    fn it_uses_program_data_on_windows() {
+      let env = MapEnvironment::new().with_var("PROGRAMDATA", "C:\\ProgramData");
+
+      assert_eq!(
+        state_home(Platform::Windows, &env).path(),
+        Some(Path::new("C:\\ProgramData"))
+      );
+    }
+  }
+}
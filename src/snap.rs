@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use crate::{Platform, Resolver, Source, home::HomeEnvironment, xdg};
+
+pub const REAL_HOME: &str = "SNAP_REAL_HOME";
+
+const COMMON: &str = "SNAP_COMMON";
+const DATA: &str = "SNAP_DATA";
+const NAME: &str = "SNAP_NAME";
+const USER_COMMON: &str = "SNAP_USER_COMMON";
+const USER_DATA: &str = "SNAP_USER_DATA";
+
+/// The snap the process is running in.
+///
+/// Strictly confined snaps run with `HOME` rewritten to `$SNAP_USER_DATA`, so [`home()`](crate::home) and every
+/// directory derived from it point inside the snap's versioned data directory. `Snap` exposes the user's real home
+/// directory alongside the directories snapd provides for the snap. To resolve every directory from the real home
+/// instead, use [`Resolver::with_snap_home()`] with [`SnapHome::Real`].
+///
+/// Returned by [`snap()`](crate::snap) and [`Resolver::snap()`].
+///
+/// # Examples
+///
+/// ```rust
+/// if let Some(snap) = dir_spec::snap() {
+///     println!("Running as the {} snap", snap.name());
+///     if let Some(home) = snap.real_home() {
+///         println!("Real home directory: {}", home.display());
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snap {
+  name: String,
+  resolver: Resolver,
+}
+
+impl Snap {
+  /// Detects a snap from `$SNAP_NAME`.
+  pub(crate) fn detect(resolver: &Resolver) -> Option<Self> {
+    if resolver.platform() != Platform::Unix {
+      return None;
+    }
+
+    let name = resolver
+      .environment()
+      .var_os(NAME)
+      .and_then(|name| name.into_string().ok())
+      .filter(|name| !name.is_empty())?;
+    Some(Self {
+      name,
+      resolver: resolver.clone(),
+    })
+  }
+
+  /// Returns the system-wide data directory shared by every revision of the snap, `$SNAP_COMMON`.
+  pub fn common(&self) -> Option<PathBuf> {
+    self.resolve(COMMON)
+  }
+
+  /// Returns the system-wide data directory of the current revision, `$SNAP_DATA`.
+  pub fn data(&self) -> Option<PathBuf> {
+    self.resolve(DATA)
+  }
+
+  /// Returns the name of the snap.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Returns the user's real home directory, `$SNAP_REAL_HOME`.
+  ///
+  /// The resolver's [`HomeLookup`](crate::HomeLookup) and [`SudoHome`](crate::SudoHome) policies apply as they do with
+  /// [`SnapHome::Real`], so under `sudo` this can be a passwd entry's home directory that agrees with
+  /// [`Resolver::home()`]. Older versions of snapd do not set the variable, and the rewritten `HOME` is never returned.
+  pub fn real_home(&self) -> Option<PathBuf> {
+    let explanation = HomeEnvironment {
+      snap_home: SnapHome::Real,
+      ..self.resolver.home_env()
+    }
+    .explain_home();
+    match explanation.source() {
+      Some(Source::Home {
+        ..
+      }) => None,
+      _ => explanation.into_path(),
+    }
+  }
+
+  /// Returns the user's data directory shared by every revision of the snap, `$SNAP_USER_COMMON`.
+  pub fn user_common(&self) -> Option<PathBuf> {
+    self.resolve(USER_COMMON)
+  }
+
+  /// Returns the user's data directory for the current revision, `$SNAP_USER_DATA`.
+  ///
+  /// Strictly confined snaps see this directory as `HOME`. snapd copies it when the snap is refreshed and restores the
+  /// previous copy when it is reverted.
+  pub fn user_data(&self) -> Option<PathBuf> {
+    self.resolve(USER_DATA)
+  }
+
+  fn resolve(&self, key: &str) -> Option<PathBuf> {
+    xdg::resolve_path(self.resolver.platform(), self.resolver.environment(), key).into_path()
+  }
+}
+
+/// Which home directory a [`Resolver`] resolves directories from inside a snap.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SnapHome {
+  /// The user's real home directory from `$SNAP_REAL_HOME`, falling back to the home directory when it is unset, as it
+  /// is outside a snap.
+  Real,
+  /// The home directory as the process sees it, which snapd rewrites to `$SNAP_USER_DATA`.
+  #[default]
+  Rewritten,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{MapEnvironment, passwd::tests::image_root};
+
+  fn snap(env: MapEnvironment) -> Option<Snap> {
+    let resolver = Resolver::new().with_platform(Platform::Unix).with_environment(env);
+    Snap::detect(&resolver)
+  }
+
+  mod detect {
+    use super::*;
+
+    #[test]
+    fn it_reads_snap_name() {
+      let snap = snap(MapEnvironment::new().with_var(NAME, "bar-app")).unwrap();
+
+      assert_eq!(snap.name(), "bar-app");
+    }
+
+    #[test]
+    fn it_returns_none_outside_a_snap() {
+      assert!(snap(MapEnvironment::new()).is_none());
+    }
+  }
+
+  mod real_home {
+    use super::*;
+    use crate::SudoHome;
+
+    #[test]
+    fn it_follows_the_sudo_home_policy() {
+      let root = image_root();
+      let env = MapEnvironment::new()
+        .with_home("/root/snap/bar-app/42")
+        .with_var(NAME, "bar-app")
+        .with_var(REAL_HOME, "/root")
+        .with_var("SUDO_USER", "alice");
+      let resolver = Resolver::new()
+        .with_platform(Platform::Unix)
+        .with_environment(env)
+        .with_passwd_root(root.path())
+        .with_sudo_home(SudoHome::Invoking);
+
+      assert_eq!(resolver.snap().unwrap().real_home(), Some(PathBuf::from("/home/alice")));
+      assert_eq!(resolver.home(), Some(PathBuf::from("/home/alice")));
+    }
+
+    #[test]
+    fn it_never_returns_the_rewritten_home() {
+      let env = MapEnvironment::new()
+        .with_home("/home/alice/snap/bar-app/42")
+        .with_var(NAME, "bar-app");
+
+      assert_eq!(snap(env).unwrap().real_home(), None);
+    }
+  }
+
+  mod user_data {
+    use super::*;
+
+    #[test]
+    fn it_separates_versioned_and_common_directories() {
+      let env = MapEnvironment::new()
+        .with_var(NAME, "bar-app")
+        .with_var(USER_COMMON, "/home/alice/snap/bar-app/common")
+        .with_var(USER_DATA, "/home/alice/snap/bar-app/42");
+      let snap = snap(env).unwrap();
+
+      assert_eq!(
+        snap.user_common(),
+        Some(PathBuf::from("/home/alice/snap/bar-app/common"))
+      );
+      assert_eq!(snap.user_data(), Some(PathBuf::from("/home/alice/snap/bar-app/42")));
+      assert_eq!(snap.data(), None);
+    }
+  }
+}
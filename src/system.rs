@@ -0,0 +1,450 @@
+//! System-wide directories shared by every user.
+//!
+//! The functions in this module resolve the locations a system-wide installation reads its configuration from and
+//! keeps its data, cache, logs and runtime files in, rather than the current user's directories:
+//! - **Linux**: `/etc`, `/var/lib`, `/var/cache`, `/var/log` and `/run`
+//! - **macOS**: `/Library/Application Support`, `/Library/Caches` and `/Library/Logs`
+//! - **Windows**: `%PROGRAMDATA%`
+//!
+//! Use [`SystemDirs`] to resolve them for an installation prefix such as `/usr/local` or `/opt/bar-app`, or with a
+//! custom [`Resolver`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use dir_spec::system;
+//!
+//! if let Some(config) = system::config() {
+//!     println!("System config directory: {}", config.display());
+//! }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::{DirError, Environment, Explanation, Platform, RejectionReason, Resolver, Source};
+
+const PROGRAMDATA: &str = "PROGRAMDATA";
+
+/// Returns the system-wide cache directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/var/cache`
+/// - **macOS**: `/Library/Caches`
+/// - **Windows**: `%PROGRAMDATA%`
+pub fn cache() -> Option<PathBuf> {
+  SystemDirs::new().cache()
+}
+
+/// Returns the system-wide configuration directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/etc`
+/// - **macOS**: `/Library/Application Support`
+/// - **Windows**: `%PROGRAMDATA%`
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::system;
+///
+/// if let Some(config) = system::config() {
+///     println!("System config directory: {}", config.display());
+/// }
+/// ```
+pub fn config() -> Option<PathBuf> {
+  SystemDirs::new().config()
+}
+
+/// Returns the system-wide data directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/var/lib`
+/// - **macOS**: `/Library/Application Support`
+/// - **Windows**: `%PROGRAMDATA%`
+pub fn data() -> Option<PathBuf> {
+  SystemDirs::new().data()
+}
+
+/// Returns the system-wide log directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/var/log`
+/// - **macOS**: `/Library/Logs`
+/// - **Windows**: `%PROGRAMDATA%`
+pub fn logs() -> Option<PathBuf> {
+  SystemDirs::new().logs()
+}
+
+/// Returns the system-wide runtime directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/run`
+/// - **macOS**: `/var/run`
+/// - **Windows**: not supported, returns `None`
+pub fn runtime() -> Option<PathBuf> {
+  SystemDirs::new().runtime()
+}
+
+/// Returns the system-wide state directory.
+///
+/// Platform defaults:
+/// - **Linux**: `/var/lib`
+/// - **macOS**: `/Library/Application Support`
+/// - **Windows**: `%PROGRAMDATA%`
+pub fn state() -> Option<PathBuf> {
+  SystemDirs::new().state()
+}
+
+/// A configurable resolver for system-wide directories.
+///
+/// Without a prefix, the directories are the platform defaults listed in the [module documentation](self). With
+/// [`with_prefix()`](Self::with_prefix), Linux and macOS follow the convention for where a package installed under that
+/// prefix keeps its files:
+/// - **`/` or `/usr`**: the platform defaults
+/// - **`/opt/<package>` on Linux**: `/etc/opt/<package>` for configuration and `/var/opt/<package>` for every other
+///   directory, as the Filesystem Hierarchy Standard keeps all of a package's variable data there
+/// - **any other prefix**, such as `/usr/local` or Homebrew's `/opt/homebrew`: `<prefix>/etc` for configuration, and
+///   `lib` for data and state, `cache`, `log` and `run` beneath `<prefix>/var`, as GNU autoconf's `sysconfdir` and
+///   `localstatedir` default to
+///
+/// A relative prefix resolves no directories. Windows has no such convention, so the prefix is ignored there.
+///
+/// # Examples
+///
+/// ```rust
+/// use dir_spec::{Platform, Resolver, SystemDirs};
+/// use std::path::PathBuf;
+///
+/// let system = SystemDirs::new()
+///   .with_prefix("/usr/local")
+///   .with_resolver(Resolver::new().with_platform(Platform::Unix));
+///
+/// assert_eq!(system.config(), Some(PathBuf::from("/usr/local/etc")));
+/// assert_eq!(system.state(), Some(PathBuf::from("/usr/local/var/lib")));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SystemDirs {
+  prefix: Option<PathBuf>,
+  resolver: Resolver,
+}
+
+impl SystemDirs {
+  /// Creates a resolver for the current platform's default system directories.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the system-wide cache directory, as described in [`cache()`].
+  pub fn cache(&self) -> Option<PathBuf> {
+    self.explain_cache().into_path()
+  }
+
+  /// Returns the system-wide configuration directory, as described in [`config()`].
+  pub fn config(&self) -> Option<PathBuf> {
+    self.explain_config().into_path()
+  }
+
+  /// Returns the system-wide data directory, as described in [`data()`].
+  pub fn data(&self) -> Option<PathBuf> {
+    self.explain_data().into_path()
+  }
+
+  /// Explains how [`cache()`](Self::cache) resolves.
+  pub fn explain_cache(&self) -> Explanation {
+    self.resolve(Kind::Cache)
+  }
+
+  /// Explains how [`config()`](Self::config) resolves.
+  pub fn explain_config(&self) -> Explanation {
+    self.resolve(Kind::Config)
+  }
+
+  /// Explains how [`data()`](Self::data) resolves.
+  pub fn explain_data(&self) -> Explanation {
+    self.resolve(Kind::Data)
+  }
+
+  /// Explains how [`logs()`](Self::logs) resolves.
+  pub fn explain_logs(&self) -> Explanation {
+    self.resolve(Kind::Logs)
+  }
+
+  /// Explains how [`runtime()`](Self::runtime) resolves.
+  pub fn explain_runtime(&self) -> Explanation {
+    self.resolve(Kind::Runtime)
+  }
+
+  /// Explains how [`state()`](Self::state) resolves.
+  pub fn explain_state(&self) -> Explanation {
+    self.resolve(Kind::State)
+  }
+
+  /// Returns the system-wide log directory, as described in [`logs()`].
+  pub fn logs(&self) -> Option<PathBuf> {
+    self.explain_logs().into_path()
+  }
+
+  /// Returns the installation prefix, if one was set.
+  pub fn prefix(&self) -> Option<&Path> {
+    self.prefix.as_deref()
+  }
+
+  /// Returns the system-wide runtime directory, as described in [`runtime()`].
+  pub fn runtime(&self) -> Option<PathBuf> {
+    self.explain_runtime().into_path()
+  }
+
+  /// Returns the system-wide state directory, as described in [`state()`].
+  pub fn state(&self) -> Option<PathBuf> {
+    self.explain_state().into_path()
+  }
+
+  /// Returns the system-wide cache directory, or the reason it could not be resolved.
+  pub fn try_cache(&self) -> Result<PathBuf, DirError> {
+    self.explain_cache().into_result()
+  }
+
+  /// Returns the system-wide configuration directory, or the reason it could not be resolved.
+  pub fn try_config(&self) -> Result<PathBuf, DirError> {
+    self.explain_config().into_result()
+  }
+
+  /// Returns the system-wide data directory, or the reason it could not be resolved.
+  pub fn try_data(&self) -> Result<PathBuf, DirError> {
+    self.explain_data().into_result()
+  }
+
+  /// Returns the system-wide log directory, or the reason it could not be resolved.
+  pub fn try_logs(&self) -> Result<PathBuf, DirError> {
+    self.explain_logs().into_result()
+  }
+
+  /// Returns the system-wide runtime directory, or the reason it could not be resolved.
+  pub fn try_runtime(&self) -> Result<PathBuf, DirError> {
+    self.explain_runtime().into_result()
+  }
+
+  /// Returns the system-wide state directory, or the reason it could not be resolved.
+  pub fn try_state(&self) -> Result<PathBuf, DirError> {
+    self.explain_state().into_result()
+  }
+
+  /// Resolves directories for a package installed under `prefix`, which must be an absolute path.
+  pub fn with_prefix(mut self, prefix: impl Into<PathBuf>) -> Self {
+    self.prefix = Some(prefix.into());
+    self
+  }
+
+  /// Resolves directories with the platform and environment of `resolver` instead of the default resolver.
+  pub fn with_resolver(mut self, resolver: Resolver) -> Self {
+    self.resolver = resolver;
+    self
+  }
+
+  fn prefixed(&self, prefix: &Path, kind: Kind) -> PathBuf {
+    let platform = self.resolver.platform();
+    if let Ok(package) = prefix.strip_prefix("/opt")
+      && platform == Platform::Unix
+      && !package.as_os_str().is_empty()
+    {
+      return match kind {
+        Kind::Config => platform.join("/etc/opt", package),
+        _ => platform.join("/var/opt", package),
+      };
+    }
+
+    let (etc, var) = (platform.join(prefix, "etc"), platform.join(prefix, "var"));
+    match kind {
+      Kind::Cache => platform.join(var, "cache"),
+      Kind::Config => etc,
+      Kind::Data | Kind::State => platform.join(var, "lib"),
+      Kind::Logs => platform.join(var, "log"),
+      Kind::Runtime => platform.join(var, "run"),
+    }
+  }
+
+  fn resolve(&self, kind: Kind) -> Explanation {
+    let platform = self.resolver.platform();
+    let prefix = self
+      .prefix
+      .as_deref()
+      .filter(|prefix| *prefix != Path::new("/") && *prefix != Path::new("/usr"));
+    match (platform, prefix) {
+      (Platform::MacOs | Platform::Unix, Some(prefix)) if !platform.is_absolute(prefix) => {
+        Explanation::unresolved(DirError::InvalidValue {
+          reason: RejectionReason::RelativePath,
+          value: prefix.as_os_str().to_os_string(),
+          variable: "prefix".to_string(),
+        })
+      }
+      (Platform::MacOs | Platform::Unix, Some(prefix)) => {
+        Explanation::resolved(self.prefixed(prefix, kind), Source::Default)
+      }
+      _ => resolve_default(platform, self.resolver.environment(), kind),
+    }
+  }
+}
+
+/// A kind of system-wide directory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Kind {
+  Cache,
+  Config,
+  Data,
+  Logs,
+  Runtime,
+  State,
+}
+
+/// Resolves the platform's default system-wide directory for `kind`, which
+/// [`Convention::Service`](crate::Convention::Service) also falls back to.
+pub(crate) fn resolve_default(platform: Platform, env: &dyn Environment, kind: Kind) -> Explanation {
+  let path = match (platform, kind) {
+    (Platform::MacOs, Kind::Cache) => "/Library/Caches",
+    (Platform::MacOs, Kind::Config | Kind::Data | Kind::State) => "/Library/Application Support",
+    (Platform::MacOs, Kind::Logs) => "/Library/Logs",
+    (Platform::MacOs, Kind::Runtime) => "/var/run",
+    (Platform::Unix, Kind::Cache) => "/var/cache",
+    (Platform::Unix, Kind::Config) => "/etc",
+    (Platform::Unix, Kind::Data | Kind::State) => "/var/lib",
+    (Platform::Unix, Kind::Logs) => "/var/log",
+    (Platform::Unix, Kind::Runtime) => "/run",
+    (Platform::Windows, Kind::Runtime) => {
+      return Explanation::unresolved(DirError::Unsupported {
+        platform,
+      });
+    }
+    (Platform::Windows, _) => return Explanation::var(platform, env, PROGRAMDATA, ""),
+  };
+
+  Explanation::resolved(path, Source::Default)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::MapEnvironment;
+
+  fn system(platform: Platform) -> SystemDirs {
+    SystemDirs::new().with_resolver(
+      Resolver::new()
+        .with_platform(platform)
+        .with_environment(MapEnvironment::new().with_var(PROGRAMDATA, "C:\\ProgramData")),
+    )
+  }
+
+  mod config {
+    use super::*;
+
+    #[test]
+    fn it_uses_etc_on_unix() {
+      assert_eq!(system(Platform::Unix).config(), Some(PathBuf::from("/etc")));
+    }
+
+    #[test]
+    fn it_uses_library_application_support_on_macos() {
+      assert_eq!(
+        system(Platform::MacOs).config(),
+        Some(PathBuf::from("/Library/Application Support"))
+      );
+    }
+
+    #[test]
+    fn it_uses_program_data_on_windows() {
+      assert_eq!(
+        system(Platform::Windows).config(),
+        Some(PathBuf::from("C:\\ProgramData"))
+      );
+    }
+  }
+
+  mod explain_runtime {
+    use super::*;
+
+    #[test]
+    fn it_reports_the_default_on_unix() {
+      let explanation = system(Platform::Unix).explain_runtime();
+
+      assert_eq!(explanation.path(), Some(Path::new("/run")));
+      assert_eq!(explanation.source(), Some(&Source::Default));
+    }
+  }
+
+  mod try_runtime {
+    use super::*;
+
+    #[test]
+    fn it_reports_unsupported_on_windows() {
+      assert_eq!(
+        system(Platform::Windows).try_runtime(),
+        Err(DirError::Unsupported {
+          platform: Platform::Windows,
+        })
+      );
+    }
+  }
+
+  mod with_prefix {
+    use super::*;
+
+    #[test]
+    fn it_follows_gnu_conventions_for_usr_local() {
+      let system = system(Platform::Unix).with_prefix("/usr/local");
+
+      assert_eq!(system.cache(), Some(PathBuf::from("/usr/local/var/cache")));
+      assert_eq!(system.config(), Some(PathBuf::from("/usr/local/etc")));
+      assert_eq!(system.data(), Some(PathBuf::from("/usr/local/var/lib")));
+      assert_eq!(system.logs(), Some(PathBuf::from("/usr/local/var/log")));
+      assert_eq!(system.runtime(), Some(PathBuf::from("/usr/local/var/run")));
+    }
+
+    #[test]
+    fn it_follows_fhs_conventions_for_opt() {
+      let system = system(Platform::Unix).with_prefix("/opt/bar-app");
+
+      assert_eq!(system.config(), Some(PathBuf::from("/etc/opt/bar-app")));
+      assert_eq!(system.data(), Some(PathBuf::from("/var/opt/bar-app")));
+      assert_eq!(system.state(), Some(PathBuf::from("/var/opt/bar-app")));
+      assert_eq!(system.logs(), Some(PathBuf::from("/var/opt/bar-app")));
+    }
+
+    #[test]
+    fn it_ignores_the_prefix_on_windows() {
+      let system = system(Platform::Windows).with_prefix("/usr/local");
+
+      assert_eq!(system.data(), Some(PathBuf::from("C:\\ProgramData")));
+    }
+
+    #[test]
+    fn it_rejects_a_relative_prefix() {
+      let system = system(Platform::Unix).with_prefix("usr/local");
+
+      assert_eq!(system.config(), None);
+      assert_eq!(
+        system.try_data(),
+        Err(DirError::InvalidValue {
+          reason: RejectionReason::RelativePath,
+          value: "usr/local".into(),
+          variable: "prefix".to_string(),
+        })
+      );
+    }
+
+    #[test]
+    fn it_uses_the_defaults_for_usr() {
+      let system = system(Platform::Unix).with_prefix("/usr");
+
+      assert_eq!(system.cache(), Some(PathBuf::from("/var/cache")));
+      assert_eq!(system.logs(), Some(PathBuf::from("/var/log")));
+      assert_eq!(system.runtime(), Some(PathBuf::from("/run")));
+    }
+
+    #[test]
+    fn it_uses_the_prefix_on_macos() {
+      let system = system(Platform::MacOs).with_prefix("/opt/homebrew");
+
+      assert_eq!(system.config(), Some(PathBuf::from("/opt/homebrew/etc")));
+      assert_eq!(system.logs(), Some(PathBuf::from("/opt/homebrew/var/log")));
+    }
+  }
+}
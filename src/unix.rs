@@ -1,80 +1,110 @@
-use std::{env, path::PathBuf};
+use std::path::PathBuf;
 
-use crate::xdg;
+use crate::{
+  Environment, Platform,
+  explain::{Explanation, Source},
+  user_dirs, xdg,
+};
 
-pub fn bin_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::BIN_HOME, ".local/bin")
+const PLATFORM: Platform = Platform::Unix;
+
+pub fn bin_home(env: &dyn Environment) -> Explanation {
+  xdg::bin_home(PLATFORM, env)
 }
 
-pub fn cache_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::CACHE_HOME, ".cache")
+pub fn cache_home(env: &dyn Environment) -> Explanation {
+  xdg::cache_home(PLATFORM, env)
 }
 
-pub fn config_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::CONFIG_HOME, ".config")
+pub fn config_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::CONFIG_DIRS).unwrap_or_else(|| vec![PathBuf::from("/etc/xdg")])
 }
 
-pub fn config_local() -> Option<PathBuf> {
-  config_home()
+pub fn config_home(env: &dyn Environment) -> Explanation {
+  xdg::config_home(PLATFORM, env)
 }
 
-pub fn data_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DATA_HOME, ".local/share")
+pub fn config_local(env: &dyn Environment) -> Explanation {
+  config_home(env)
 }
 
-pub fn data_local() -> Option<PathBuf> {
-  data_home()
+pub fn data_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::DATA_DIRS)
+    .unwrap_or_else(|| vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")])
 }
 
-pub fn desktop() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DESKTOP_DIR, "Desktop")
+pub fn data_home(env: &dyn Environment) -> Explanation {
+  xdg::data_home(PLATFORM, env)
 }
 
-pub fn documents() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DOCUMENTS_DIR, "Documents")
+pub fn data_local(env: &dyn Environment) -> Explanation {
+  data_home(env)
 }
 
-pub fn downloads() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::DOWNLOAD_DIR, "Downloads")
+pub fn desktop(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::DESKTOP_DIR, "Desktop")
 }
 
-pub fn fonts() -> Option<PathBuf> {
-  env::home_dir().map(|p| p.join(".local/share/fonts"))
+pub fn documents(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::DOCUMENTS_DIR, "Documents")
 }
 
-pub fn music() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::MUSIC_DIR, "Music")
+pub fn downloads(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::DOWNLOAD_DIR, "Downloads")
 }
 
-pub fn pictures() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::PICTURES_DIR, "Pictures")
+pub fn fonts(env: &dyn Environment) -> Explanation {
+  xdg::resolve_home(PLATFORM, env, ".local/share/fonts")
 }
 
-pub fn preferences() -> Option<PathBuf> {
-  config_home()
+pub fn logs(env: &dyn Environment) -> Explanation {
+  state_home(env)
 }
 
-pub fn publicshare() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::PUBLICSHARE_DIR, "Public")
+pub fn music(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::MUSIC_DIR, "Music")
 }
 
-pub fn runtime() -> Option<PathBuf> {
-  xdg::resolve_path(xdg::RUNTIME_DIR).or_else(|| {
-    env::var("TMPDIR")
-      .ok()
-      .map(PathBuf::from)
-      .or_else(|| Some(PathBuf::from("/tmp")))
-  })
+pub fn pictures(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::PICTURES_DIR, "Pictures")
+}
+
+pub fn preferences(env: &dyn Environment) -> Explanation {
+  config_home(env)
+}
+
+pub fn publicshare(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::PUBLICSHARE_DIR, "Public")
 }
 
-pub fn state_home() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::STATE_HOME, ".local/state")
+pub fn runtime(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, xdg::RUNTIME_DIR)
+    .or_else(|| Explanation::var(PLATFORM, env, "TMPDIR", ""))
+    .or_else(|| Explanation::resolved("/tmp", Source::Default))
 }
 
-pub fn templates() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::TEMPLATES_DIR, "Templates")
+pub fn state_home(env: &dyn Environment) -> Explanation {
+  xdg::state_home(PLATFORM, env)
 }
 
-pub fn videos() -> Option<PathBuf> {
-  xdg::resolve_path_with_fallback(xdg::VIDEOS_DIR, "Videos")
+pub fn templates(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::TEMPLATES_DIR, "Templates")
+}
+
+pub fn videos(env: &dyn Environment) -> Explanation {
+  resolve_user_dir(env, xdg::VIDEOS_DIR, "Videos")
+}
+
+fn resolve_user_dir(env: &dyn Environment, key: &str, default: &str) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, key).or_else(|| {
+    let home = env.home_dir();
+    user_dirs::resolve(
+      env.root(),
+      config_home(env).path(),
+      &config_dirs(env),
+      home.as_deref(),
+      key,
+    )
+    .unwrap_or_else(|| xdg::resolve_home(PLATFORM, env, default))
+  })
 }
@@ -0,0 +1,532 @@
+use std::{
+  collections::HashMap,
+  ffi::OsString,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  environment,
+  explain::{Explanation, Source},
+};
+
+pub const CONFIG_FILE_NAME: &str = "user-dirs.conf";
+pub const DEFAULTS_FILE_NAME: &str = "user-dirs.defaults";
+pub const FILE_NAME: &str = "user-dirs.dirs";
+
+const DESKTOP_DIR: &str = "XDG_DESKTOP_DIR";
+const HOME_VAR: &[u8] = b"$HOME";
+const HOME_VAR_BRACED: &[u8] = b"${HOME}";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+  pub enabled: bool,
+  pub utf8_filenames: bool,
+}
+
+impl Config {
+  pub fn load(root: Option<&Path>, config_home: Option<&Path>, config_dirs: &[PathBuf]) -> Self {
+    let mut config = Self::default();
+    for dir in config_dirs.iter().rev().map(PathBuf::as_path).chain(config_home) {
+      if let Ok(contents) = fs::read(environment::beneath(root, &dir.join(CONFIG_FILE_NAME))) {
+        config.merge(&contents);
+      }
+    }
+    config
+  }
+
+  fn merge(&mut self, contents: &[u8]) {
+    for (key, value) in assignments(contents) {
+      let value = String::from_utf8_lossy(value);
+      match key {
+        b"enabled" => self.enabled = !value.eq_ignore_ascii_case("false"),
+        b"filename_encoding" => {
+          self.utf8_filenames = value.eq_ignore_ascii_case("utf-8") || value.eq_ignore_ascii_case("utf8");
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      utf8_filenames: true,
+    }
+  }
+}
+
+pub fn parse(contents: &[u8], home: &Path, config: &Config) -> HashMap<String, PathBuf> {
+  let mut dirs = HashMap::new();
+  for (key, value) in assignments(contents) {
+    let value = unquote(value);
+    if config.utf8_filenames && str::from_utf8(&value).is_err() {
+      continue;
+    }
+    if let Some(path) = expand(&value, home) {
+      dirs.insert(String::from_utf8_lossy(key).into_owned(), path);
+    }
+  }
+  dirs
+}
+
+pub fn parse_defaults(contents: &[u8]) -> HashMap<String, PathBuf> {
+  assignments(contents)
+    .map(|(key, value)| {
+      let name = String::from_utf8_lossy(key).into_owned();
+      (name, PathBuf::from(os_string(unquote(value))))
+    })
+    .filter(|(_, path)| path.is_relative() && !path.as_os_str().is_empty())
+    .collect()
+}
+
+/// Resolves `key` from the `user-dirs` files, reading them beneath `root` if set.
+///
+/// The returned paths are as seen from inside `root`, while the reported source is the file that was read.
+pub fn resolve(
+  root: Option<&Path>,
+  config_home: Option<&Path>,
+  config_dirs: &[PathBuf],
+  home: Option<&Path>,
+  key: &str,
+) -> Option<Explanation> {
+  let home = home?;
+  let config = Config::load(root, config_home, config_dirs);
+
+  if let Some(file) = config_home.map(|dir| environment::beneath(root, &dir.join(FILE_NAME)))
+    && let Some(path) = fs::read(&file)
+      .ok()
+      .and_then(|contents| parse(&contents, home, &config).remove(key))
+  {
+    return Some(Explanation::resolved(
+      path,
+      Source::UserDirs {
+        file,
+        key: key.to_string(),
+      },
+    ));
+  }
+
+  if !config.enabled {
+    return Some(unconfigured(home, key));
+  }
+
+  let (file, defaults) = config_dirs.iter().find_map(|dir| {
+    let file = environment::beneath(root, &dir.join(DEFAULTS_FILE_NAME));
+    fs::read(&file).ok().map(|contents| (file, contents))
+  })?;
+  let name = key.trim_start_matches("XDG_").trim_end_matches("_DIR");
+  Some(parse_defaults(&defaults).remove(name).map_or_else(
+    || unconfigured(home, key),
+    |relative| {
+      Explanation::resolved(
+        home.join(relative),
+        Source::UserDirs {
+          file,
+          key: key.to_string(),
+        },
+      )
+    },
+  ))
+}
+
+fn assignments(contents: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+  contents.split(|b| *b == b'\n').filter_map(|line| {
+    let line = line.trim_ascii();
+    if line.is_empty() || line.starts_with(b"#") {
+      return None;
+    }
+
+    let separator = line.iter().position(|b| *b == b'=')?;
+    Some((line[..separator].trim_ascii(), line[separator + 1..].trim_ascii()))
+  })
+}
+
+fn expand(value: &[u8], home: &Path) -> Option<PathBuf> {
+  for var in [HOME_VAR_BRACED, HOME_VAR] {
+    if let Some(rest) = value.strip_prefix(var) {
+      return match rest.strip_prefix(b"/") {
+        Some(relative) => {
+          let separators = relative.iter().take_while(|b| **b == b'/').count();
+          Some(home.join(os_string(relative[separators..].to_vec())))
+        }
+        None if rest.is_empty() => Some(home.to_path_buf()),
+        None => None,
+      };
+    }
+  }
+
+  Some(PathBuf::from(os_string(value.to_vec()))).filter(|p| p.is_absolute())
+}
+
+#[cfg(unix)]
+fn os_string(bytes: Vec<u8>) -> OsString {
+  use std::os::unix::ffi::OsStringExt;
+
+  OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn os_string(bytes: Vec<u8>) -> OsString {
+  OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn unconfigured(home: &Path, key: &str) -> Explanation {
+  if key == DESKTOP_DIR {
+    Explanation::resolved(home.join("Desktop"), Source::home("Desktop"))
+  } else {
+    Explanation::resolved(home, Source::home(""))
+  }
+}
+
+fn unquote(value: &[u8]) -> Vec<u8> {
+  let Some(quoted) = value.strip_prefix(b"\"") else {
+    return value.to_vec();
+  };
+
+  let mut unquoted = Vec::with_capacity(quoted.len());
+  let mut bytes = quoted.iter();
+  while let Some(b) = bytes.next() {
+    match b {
+      b'"' => break,
+      b'\\' => unquoted.extend(bytes.next()),
+      _ => unquoted.push(*b),
+    }
+  }
+  unquoted
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+
+  mod config {
+    use super::*;
+
+    #[test]
+    fn it_defaults_to_enabled_utf8() {
+      let config = Config::load(None, None, &[]);
+
+      assert_eq!(config, Config::default());
+      assert!(config.enabled);
+      assert!(config.utf8_filenames);
+    }
+
+    #[test]
+    fn it_lets_user_config_override_system_config() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let user = root.join("user");
+      let system = root.join("system");
+      fs::create_dir_all(&user).unwrap();
+      fs::create_dir_all(&system).unwrap();
+      fs::write(
+        system.join(CONFIG_FILE_NAME),
+        "enabled=False\nfilename_encoding=locale\n",
+      )
+      .unwrap();
+      fs::write(user.join(CONFIG_FILE_NAME), "enabled=True\n").unwrap();
+
+      let config = Config::load(None, Some(&user), &[system]);
+
+      assert!(config.enabled);
+      assert!(!config.utf8_filenames);
+    }
+
+    #[test]
+    fn it_reads_disabled_system_config() {
+      let temp = tempfile::tempdir().unwrap();
+      let system = temp.path();
+      fs::write(system.join(CONFIG_FILE_NAME), "# comment\nenabled=False\n").unwrap();
+
+      let config = Config::load(None, None, &[system.to_path_buf()]);
+
+      assert!(!config.enabled);
+    }
+  }
+
+  mod parse {
+    use super::*;
+
+    #[test]
+    fn it_accepts_absolute_values() {
+      let contents = r#"XDG_PUBLICSHARE_DIR="/srv/public""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(dirs.get("XDG_PUBLICSHARE_DIR"), Some(&PathBuf::from("/srv/public")));
+    }
+
+    #[test]
+    fn it_expands_braced_home_prefix() {
+      let contents = r#"XDG_MUSIC_DIR="${HOME}/Musik""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(dirs.get("XDG_MUSIC_DIR"), Some(&PathBuf::from("/home/user/Musik")));
+    }
+
+    #[test]
+    fn it_expands_home_prefix() {
+      let contents = r#"XDG_DOWNLOAD_DIR="$HOME/Téléchargements""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(
+        dirs.get("XDG_DOWNLOAD_DIR"),
+        Some(&PathBuf::from("/home/user/Téléchargements"))
+      );
+    }
+
+    #[test]
+    fn it_handles_escaped_characters() {
+      let contents = r#"XDG_DOCUMENTS_DIR="$HOME/My \"Docs\" \\ Files""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(
+        dirs.get("XDG_DOCUMENTS_DIR"),
+        Some(&PathBuf::from(r#"/home/user/My "Docs" \ Files"#))
+      );
+    }
+
+    #[test]
+    fn it_ignores_comments_and_blank_lines() {
+      let contents =
+        "# This file is written by xdg-user-dirs-update\n\n  # indented comment\nXDG_DESKTOP_DIR=\"$HOME/Bureau\"\n";
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(dirs.len(), 1);
+      assert_eq!(dirs.get("XDG_DESKTOP_DIR"), Some(&PathBuf::from("/home/user/Bureau")));
+    }
+
+    #[test]
+    fn it_ignores_relative_values() {
+      let contents = r#"XDG_VIDEOS_DIR="Videos""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert!(dirs.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_unknown_variables_in_prefix() {
+      let contents = r#"XDG_VIDEOS_DIR="$HOMEDIR/Videos""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert!(dirs.is_empty());
+    }
+
+    #[test]
+    fn it_keeps_locale_encoded_names_when_encoding_is_locale() {
+      let contents = b"XDG_MUSIC_DIR=\"$HOME/M\xfasica\"";
+      let config = Config {
+        enabled: true,
+        utf8_filenames: false,
+      };
+
+      let dirs = parse(contents, Path::new("/home/user"), &config);
+
+      assert_eq!(
+        dirs.get("XDG_MUSIC_DIR"),
+        Some(&Path::new("/home/user").join(os_string(b"M\xfasica".to_vec())))
+      );
+    }
+
+    #[test]
+    fn it_maps_bare_home_to_home_directory() {
+      let contents = r#"XDG_TEMPLATES_DIR="$HOME/""#;
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(dirs.get("XDG_TEMPLATES_DIR"), Some(&PathBuf::from("/home/user")));
+    }
+
+    #[test]
+    fn it_rejects_non_utf8_names_when_encoding_is_utf8() {
+      let contents = b"XDG_MUSIC_DIR=\"$HOME/M\xfasica\"";
+
+      let dirs = parse(contents, Path::new("/home/user"), &Config::default());
+
+      assert!(dirs.is_empty());
+    }
+
+    #[test]
+    fn it_uses_the_last_assignment() {
+      let contents = "XDG_MUSIC_DIR=\"$HOME/First\"\nXDG_MUSIC_DIR=\"$HOME/Second\"\n";
+
+      let dirs = parse(contents.as_bytes(), Path::new("/home/user"), &Config::default());
+
+      assert_eq!(dirs.get("XDG_MUSIC_DIR"), Some(&PathBuf::from("/home/user/Second")));
+    }
+  }
+
+  mod parse_defaults {
+    use super::*;
+
+    #[test]
+    fn it_ignores_absolute_and_empty_values() {
+      let contents = b"DESKTOP=/srv/desktop\nMUSIC=\n";
+
+      let defaults = parse_defaults(contents);
+
+      assert!(defaults.is_empty());
+    }
+
+    #[test]
+    fn it_reads_relative_names() {
+      let contents = b"# Default settings\nDESKTOP=Desktop\nMUSIC=Documents/Music\n";
+
+      let defaults = parse_defaults(contents);
+
+      assert_eq!(defaults.get("DESKTOP"), Some(&PathBuf::from("Desktop")));
+      assert_eq!(defaults.get("MUSIC"), Some(&PathBuf::from("Documents/Music")));
+    }
+  }
+
+  mod resolve {
+    use super::*;
+
+    #[test]
+    fn it_falls_back_to_home_when_disabled() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let user = root.join("user");
+      let system = root.join("system");
+      fs::create_dir_all(&user).unwrap();
+      fs::create_dir_all(&system).unwrap();
+      fs::write(system.join(CONFIG_FILE_NAME), "enabled=False\n").unwrap();
+      fs::write(system.join(DEFAULTS_FILE_NAME), "PUBLICSHARE=Public\n").unwrap();
+      let home = Path::new("/home/user");
+
+      let public = resolve(
+        None,
+        Some(&user),
+        std::slice::from_ref(&system),
+        Some(home),
+        "XDG_PUBLICSHARE_DIR",
+      );
+      let desktop = resolve(None, Some(&user), &[system], Some(home), DESKTOP_DIR);
+
+      assert_eq!(
+        public.and_then(Explanation::into_path),
+        Some(PathBuf::from("/home/user"))
+      );
+      assert_eq!(
+        desktop.and_then(Explanation::into_path),
+        Some(PathBuf::from("/home/user/Desktop"))
+      );
+    }
+
+    #[test]
+    fn it_falls_back_to_home_when_missing_from_defaults() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let system = root.join("system");
+      fs::create_dir_all(&system).unwrap();
+      fs::write(system.join(DEFAULTS_FILE_NAME), "DESKTOP=Desktop\n").unwrap();
+
+      let result = resolve(
+        None,
+        None,
+        &[system],
+        Some(Path::new("/home/user")),
+        "XDG_TEMPLATES_DIR",
+      );
+
+      assert_eq!(
+        result.and_then(Explanation::into_path),
+        Some(PathBuf::from("/home/user"))
+      );
+    }
+
+    #[test]
+    fn it_prefers_user_dirs_over_defaults() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let user = root.join("user");
+      let system = root.join("system");
+      fs::create_dir_all(&user).unwrap();
+      fs::create_dir_all(&system).unwrap();
+      fs::write(user.join(FILE_NAME), "XDG_MUSIC_DIR=\"$HOME/Musique\"\n").unwrap();
+      fs::write(system.join(DEFAULTS_FILE_NAME), "MUSIC=Music\n").unwrap();
+
+      let result = resolve(
+        None,
+        Some(&user),
+        &[system],
+        Some(Path::new("/home/user")),
+        "XDG_MUSIC_DIR",
+      );
+
+      assert_eq!(
+        result.and_then(Explanation::into_path),
+        Some(PathBuf::from("/home/user/Musique"))
+      );
+    }
+
+    #[test]
+    fn it_reports_the_file_an_entry_came_from() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let user = root.join("user");
+      fs::create_dir_all(&user).unwrap();
+      fs::write(user.join(FILE_NAME), "XDG_MUSIC_DIR=\"$HOME/Musique\"\n").unwrap();
+
+      let result = resolve(None, Some(&user), &[], Some(Path::new("/home/user")), "XDG_MUSIC_DIR").unwrap();
+
+      assert_eq!(
+        result.source(),
+        Some(&Source::UserDirs {
+          file: user.join(FILE_NAME),
+          key: "XDG_MUSIC_DIR".to_string(),
+        })
+      );
+    }
+
+    #[test]
+    fn it_returns_none_without_any_configuration() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+
+      let result = resolve(
+        None,
+        Some(root),
+        &[root.to_path_buf()],
+        Some(Path::new("/home/user")),
+        "XDG_MUSIC_DIR",
+      );
+
+      assert_eq!(result, None);
+    }
+
+    #[test]
+    fn it_uses_first_defaults_file_in_config_dirs() {
+      let temp = tempfile::tempdir().unwrap();
+      let root = temp.path();
+      let first = root.join("first");
+      let second = root.join("second");
+      fs::create_dir_all(&first).unwrap();
+      fs::create_dir_all(&second).unwrap();
+      fs::write(first.join(DEFAULTS_FILE_NAME), "MUSIC=Documents/Music\n").unwrap();
+      fs::write(second.join(DEFAULTS_FILE_NAME), "MUSIC=Music\n").unwrap();
+
+      let result = resolve(
+        None,
+        None,
+        &[first, second],
+        Some(Path::new("/home/user")),
+        "XDG_MUSIC_DIR",
+      );
+
+      assert_eq!(
+        result.and_then(Explanation::into_path),
+        Some(PathBuf::from("/home/user/Documents/Music"))
+      );
+    }
+  }
+}
@@ -1,91 +1,119 @@
-use std::{env, path::PathBuf};
+use std::path::PathBuf;
 
-use crate::xdg;
+use crate::{
+  DirError, Environment, Platform,
+  explain::{Explanation, Source},
+  xdg,
+};
 
 const APPDATA: &str = "APPDATA";
 const LOCALAPPDATA: &str = "LOCALAPPDATA";
+const PLATFORM: Platform = Platform::Windows;
+const PROGRAMDATA: &str = "PROGRAMDATA";
 const USERPROFILE: &str = "USERPROFILE";
 
-pub fn bin_home() -> Option<PathBuf> {
-  xdg::resolve_path(xdg::BIN_HOME).or_else(|| resolve_path(LOCALAPPDATA).map(|p| p.join("Programs")))
+pub fn bin_home(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::BIN_HOME, LOCALAPPDATA, "Programs")
 }
 
-pub fn cache_home() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback(xdg::CACHE_HOME, LOCALAPPDATA)
+pub fn cache_home(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback(env, xdg::CACHE_HOME, LOCALAPPDATA)
 }
 
-pub fn config_home() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback(xdg::CONFIG_HOME, APPDATA)
+pub fn config_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::CONFIG_DIRS)
+    .unwrap_or_else(|| resolve_path(env, PROGRAMDATA).into_iter().collect())
 }
 
-pub fn config_local() -> Option<PathBuf> {
-  resolve_path(LOCALAPPDATA)
+pub fn config_home(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback(env, xdg::CONFIG_HOME, APPDATA)
 }
 
-pub fn data_home() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback(xdg::DATA_HOME, APPDATA)
+pub fn config_local(env: &dyn Environment) -> Explanation {
+  Explanation::var(PLATFORM, env, LOCALAPPDATA, "")
 }
 
-pub fn data_local() -> Option<PathBuf> {
-  env::var_os(LOCALAPPDATA).map(PathBuf::from)
+pub fn data_dirs(env: &dyn Environment) -> Vec<PathBuf> {
+  xdg::resolve_paths(PLATFORM, env, xdg::DATA_DIRS)
+    .unwrap_or_else(|| resolve_path(env, PROGRAMDATA).into_iter().collect())
 }
 
-pub fn desktop() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::DESKTOP_DIR, USERPROFILE, "Desktop")
+pub fn data_home(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback(env, xdg::DATA_HOME, APPDATA)
 }
 
-pub fn documents() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::DOCUMENTS_DIR, USERPROFILE, "Documents")
+pub fn data_local(env: &dyn Environment) -> Explanation {
+  Explanation::var(PLATFORM, env, LOCALAPPDATA, "")
 }
 
-pub fn downloads() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::DOWNLOAD_DIR, USERPROFILE, "Downloads")
+pub fn desktop(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::DESKTOP_DIR, USERPROFILE, "Desktop")
 }
 
-pub fn fonts() -> Option<PathBuf> {
-  None
+pub fn documents(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::DOCUMENTS_DIR, USERPROFILE, "Documents")
 }
 
-pub fn music() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::MUSIC_DIR, USERPROFILE, "Music")
+pub fn downloads(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::DOWNLOAD_DIR, USERPROFILE, "Downloads")
 }
 
-pub fn pictures() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::PICTURES_DIR, USERPROFILE, "Pictures")
+pub fn fonts(_env: &dyn Environment) -> Explanation {
+  Explanation::unresolved(DirError::Unsupported {
+    platform: PLATFORM,
+  })
 }
 
-pub fn preferences() -> Option<PathBuf> {
-  config_home()
+pub fn logs(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback(env, xdg::STATE_HOME, LOCALAPPDATA)
 }
 
-pub fn publicshare() -> Option<PathBuf> {
-  xdg::resolve_path(xdg::PUBLICSHARE_DIR).or_else(|| Some(PathBuf::from("C:\\Users\\Public")))
+pub fn music(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::MUSIC_DIR, USERPROFILE, "Music")
 }
 
-pub fn runtime() -> Option<PathBuf> {
-  xdg::resolve_path(xdg::RUNTIME_DIR).or_else(|| env::var_os("TEMP").map(PathBuf::from))
+pub fn pictures(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::PICTURES_DIR, USERPROFILE, "Pictures")
 }
 
-pub fn state_home() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback(xdg::STATE_HOME, LOCALAPPDATA)
+pub fn preferences(env: &dyn Environment) -> Explanation {
+  config_home(env)
 }
 
-pub fn templates() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::TEMPLATES_DIR, USERPROFILE, "Templates")
+pub fn publicshare(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, xdg::PUBLICSHARE_DIR)
+    .or_else(|| Explanation::resolved("C:\\Users\\Public", Source::Default))
 }
 
-pub fn videos() -> Option<PathBuf> {
-  resolve_xdg_path_with_fallback_and_sub_dir(xdg::VIDEOS_DIR, USERPROFILE, "Videos")
+pub fn runtime(env: &dyn Environment) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, xdg::RUNTIME_DIR).or_else(|| Explanation::var(PLATFORM, env, "TEMP", ""))
 }
 
-fn resolve_path(key: &str) -> Option<PathBuf> {
-  env::var_os(key).map(PathBuf::from)
+pub fn state_home(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback(env, xdg::STATE_HOME, LOCALAPPDATA)
 }
 
-fn resolve_xdg_path_with_fallback(xdg_key: &str, key: &str) -> Option<PathBuf> {
-  xdg::resolve_path(xdg_key).or_else(|| env::var_os(key).map(PathBuf::from))
+pub fn templates(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::TEMPLATES_DIR, USERPROFILE, "Templates")
 }
 
-fn resolve_xdg_path_with_fallback_and_sub_dir(xdg_key: &str, key: &str, sub_dir: &str) -> Option<PathBuf> {
-  xdg::resolve_path(xdg_key).or_else(|| env::var_os(key).map(|p| PathBuf::from(p).join(sub_dir)))
+pub fn videos(env: &dyn Environment) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg::VIDEOS_DIR, USERPROFILE, "Videos")
+}
+
+fn resolve_path(env: &dyn Environment, key: &str) -> Option<PathBuf> {
+  env.var_os(key).map(PathBuf::from)
+}
+
+fn resolve_xdg_path_with_fallback(env: &dyn Environment, xdg_key: &str, key: &str) -> Explanation {
+  resolve_xdg_path_with_fallback_and_sub_dir(env, xdg_key, key, "")
+}
+
+fn resolve_xdg_path_with_fallback_and_sub_dir(
+  env: &dyn Environment,
+  xdg_key: &str,
+  key: &str,
+  sub_dir: &str,
+) -> Explanation {
+  xdg::resolve_path(PLATFORM, env, xdg_key).or_else(|| Explanation::var(PLATFORM, env, key, sub_dir))
 }
@@ -1,8 +1,15 @@
-use std::{env, path::PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+  DirError, Environment, Platform, environment,
+  explain::{Explanation, Rejection, RejectionReason, Source},
+};
 
 pub const BIN_HOME: &str = "XDG_BIN_HOME";
 pub const CACHE_HOME: &str = "XDG_CACHE_HOME";
+pub const CONFIG_DIRS: &str = "XDG_CONFIG_DIRS";
 pub const CONFIG_HOME: &str = "XDG_CONFIG_HOME";
+pub const DATA_DIRS: &str = "XDG_DATA_DIRS";
 pub const DATA_HOME: &str = "XDG_DATA_HOME";
 pub const DESKTOP_DIR: &str = "XDG_DESKTOP_DIR";
 pub const DOCUMENTS_DIR: &str = "XDG_DOCUMENTS_DIR";
@@ -15,10 +22,101 @@ pub const STATE_HOME: &str = "XDG_STATE_HOME";
 pub const TEMPLATES_DIR: &str = "XDG_TEMPLATES_DIR";
 pub const VIDEOS_DIR: &str = "XDG_VIDEOS_DIR";
 
-pub fn resolve_path(key: &str) -> Option<PathBuf> {
-  env::var_os(key).map(PathBuf::from).filter(|p| p.is_absolute())
+pub fn bin_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve_path_with_fallback(platform, env, BIN_HOME, ".local/bin")
+}
+
+pub fn cache_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve_path_with_fallback(platform, env, CACHE_HOME, ".cache")
+}
+
+pub fn config_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve_path_with_fallback(platform, env, CONFIG_HOME, ".config")
+}
+
+pub fn data_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve_path_with_fallback(platform, env, DATA_HOME, ".local/share")
+}
+
+/// Returns every existing `relative` beneath `home` and `dirs`, checked beneath `root` but reported as seen inside it.
+pub fn find_files(
+  platform: Platform,
+  root: Option<&Path>,
+  home: Option<PathBuf>,
+  dirs: Vec<PathBuf>,
+  relative: &Path,
+) -> Vec<PathBuf> {
+  if !is_nested(platform, relative) {
+    return Vec::new();
+  }
+
+  let mut found: Vec<PathBuf> = Vec::new();
+  for candidate in home.into_iter().chain(dirs).map(|dir| dir.join(relative)) {
+    if environment::beneath(root, &candidate).exists() && !found.contains(&candidate) {
+      found.push(candidate);
+    }
+  }
+  found
+}
+
+/// Returns `true` if joining `relative` onto a directory can only name something beneath it.
+///
+/// Rejects `..`, roots and prefixes, including Windows root-relative and drive-relative paths such as `\foo` and
+/// `C:foo` when resolving for Windows from another host.
+fn is_nested(platform: Platform, relative: &Path) -> bool {
+  relative.components().all(|component| match component {
+    Component::Normal(name) => platform != Platform::Windows || !name.to_string_lossy().contains(['\\', ':']),
+    _ => false,
+  })
+}
+
+pub fn resolve_home(platform: Platform, env: &dyn Environment, relative: &str) -> Explanation {
+  env
+    .home_dir()
+    .map_or(Explanation::unresolved(DirError::MissingHome), |home| {
+      let path = if relative.is_empty() {
+        home
+      } else {
+        platform.join(home, relative)
+      };
+      Explanation::resolved(path, Source::home(relative))
+    })
+}
+
+pub fn resolve_path(platform: Platform, env: &dyn Environment, key: &str) -> Explanation {
+  let missing = || {
+    Explanation::unresolved(DirError::MissingVariable {
+      variable: key.to_string(),
+    })
+  };
+  let Some(value) = env.var_os(key) else {
+    return missing();
+  };
+
+  if value.is_empty() {
+    missing().reject(Rejection::new(key, value, RejectionReason::Empty))
+  } else if !platform.is_representable(&value) {
+    missing().reject(Rejection::new(key, value, RejectionReason::NotUnicode))
+  } else if platform.is_absolute(Path::new(&value)) {
+    Explanation::resolved(&value, Source::var(key, value.clone(), ""))
+  } else {
+    missing().reject(Rejection::new(key, value, RejectionReason::RelativePath))
+  }
+}
+
+pub fn resolve_path_with_fallback(platform: Platform, env: &dyn Environment, key: &str, default: &str) -> Explanation {
+  resolve_path(platform, env, key).or_else(|| resolve_home(platform, env, default))
+}
+
+pub fn resolve_paths(platform: Platform, env: &dyn Environment, key: &str) -> Option<Vec<PathBuf>> {
+  let paths: Vec<PathBuf> = platform
+    .split_paths(&env.var_os(key)?)
+    .into_iter()
+    .filter(|p| platform.is_absolute(p))
+    .collect();
+  if paths.is_empty() { None } else { Some(paths) }
 }
 
-pub fn resolve_path_with_fallback(key: &str, default: &str) -> Option<PathBuf> {
-  resolve_path(key).or_else(|| env::home_dir().map(|p| p.join(default)))
+pub fn state_home(platform: Platform, env: &dyn Environment) -> Explanation {
+  resolve_path_with_fallback(platform, env, STATE_HOME, ".local/state")
 }